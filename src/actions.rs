@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{extract_jira_key, parse_date, JiraIssue, JiraLinkedIssue, JiraStatus, OutageRecord};
+
+/// Link types that point at the same problem rather than at follow-up work.
+const IGNORED_RELATIONSHIPS: &[&str] = &["duplicates", "is duplicated by", "clones", "is cloned by"];
+
+/// A follow-up issue linked from an incident's JIRA issue.
+#[derive(Debug, Clone)]
+pub struct PreventativeAction {
+    pub key: String,
+    pub summary: Option<String>,
+    /// How the incident relates to the action, e.g. "is caused by" or "subtask"
+    pub relationship: String,
+    pub status: Option<String>,
+    pub done: bool,
+    pub assignee: Option<String>,
+}
+
+impl PreventativeAction {
+    fn describe(&self) -> String {
+        let mut line = self.key.clone();
+        if let Some(summary) = &self.summary {
+            line.push_str(&format!(" {}", summary.trim()));
+        }
+        line.push_str(&self.progress());
+        line
+    }
+
+    /// The part after the key and summary, e.g. " (is caused by) - In Progress, Jane Doe".
+    pub fn progress(&self) -> String {
+        format!(
+            " ({}) - {}, {}",
+            self.relationship,
+            self.status.as_deref().unwrap_or("status unknown"),
+            self.assignee.as_deref().unwrap_or("unassigned")
+        )
+    }
+}
+
+fn is_done(status: Option<&JiraStatus>) -> bool {
+    status
+        .and_then(|status| status.category.as_ref())
+        .is_some_and(|category| category.key == "done")
+}
+
+/// Every issue linked from the record's JIRA issue, with the relationship as seen from the incident.
+fn linked_issues<'a>(
+    record: &OutageRecord,
+    jira_details: &'a HashMap<String, JiraIssue>,
+) -> Vec<(String, &'a JiraLinkedIssue)> {
+    let Some(issue) = extract_jira_key(&record.ticket).and_then(|key| jira_details.get(&key)) else {
+        return Vec::new();
+    };
+
+    let links = issue.fields.issuelinks.iter().filter_map(|link| {
+        match (&link.inward_issue, &link.outward_issue) {
+            (Some(linked), _) => Some((link.link_type.inward.clone(), linked)),
+            (None, Some(linked)) => Some((link.link_type.outward.clone(), linked)),
+            (None, None) => None,
+        }
+    });
+    let subtasks = issue.fields.subtasks.iter().map(|subtask| ("subtask".to_string(), subtask));
+
+    links
+        .chain(subtasks)
+        .filter(|(relationship, _)| !IGNORED_RELATIONSHIPS.contains(&relationship.to_lowercase().as_str()))
+        .collect()
+}
+
+/// Keys of the issues linked from the outages' JIRA issues, in order and without repeats.
+pub fn linked_issue_keys(outages: &[OutageRecord], jira_details: &HashMap<String, JiraIssue>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    outages
+        .iter()
+        .flat_map(|record| linked_issues(record, jira_details))
+        .map(|(_, linked)| linked.key.clone())
+        .filter(|key| seen.insert(key.clone()))
+        .collect()
+}
+
+/// The record's follow-up actions. A fetched copy of the linked issue is preferred,
+/// falling back to the summary and status embedded in the link.
+pub fn incident_actions(record: &OutageRecord, jira_details: &HashMap<String, JiraIssue>) -> Vec<PreventativeAction> {
+    linked_issues(record, jira_details)
+        .into_iter()
+        .map(|(relationship, linked)| {
+            let embedded = linked.fields.as_ref();
+            let fetched = jira_details.get(&linked.key).map(|issue| &issue.fields);
+            let summary = fetched
+                .and_then(|fields| fields.summary.clone())
+                .or_else(|| embedded.and_then(|fields| fields.summary.clone()));
+            let status = fetched
+                .and_then(|fields| fields.status.as_ref())
+                .or_else(|| embedded.and_then(|fields| fields.status.as_ref()));
+
+            PreventativeAction {
+                key: linked.key.clone(),
+                summary,
+                relationship,
+                status: status.map(|status| status.name.clone()),
+                done: is_done(status),
+                assignee: fetched.and_then(|fields| fields.assignee.as_ref()).map(|user| user.display_name.clone()),
+            }
+        })
+        .collect()
+}
+
+/// This week's incidents with their actions, and earlier incidents' actions that are still open.
+pub struct ActionsReport<'a> {
+    pub incidents: Vec<(&'a OutageRecord, Vec<PreventativeAction>)>,
+    pub carried_over: Vec<(&'a OutageRecord, PreventativeAction)>,
+}
+
+impl ActionsReport<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.incidents.is_empty() && self.carried_over.is_empty()
+    }
+}
+
+/// Collects the actions for this week's incidents that have a JIRA issue. An open action from earlier
+/// weeks is carried over once, under the most recent incident it's linked to, unless this week lists it.
+pub fn collect_actions<'a>(
+    outages: &'a [OutageRecord],
+    history_outages: &'a [OutageRecord],
+    jira_details: &HashMap<String, JiraIssue>,
+) -> ActionsReport<'a> {
+    let mut listed = HashSet::new();
+
+    let incidents: Vec<(&OutageRecord, Vec<PreventativeAction>)> = outages
+        .iter()
+        .filter(|record| extract_jira_key(&record.ticket).is_some())
+        .map(|record| (record, incident_actions(record, jira_details)))
+        .collect();
+    for (_, actions) in &incidents {
+        listed.extend(actions.iter().map(|action| action.key.clone()));
+    }
+
+    let mut carried_over = Vec::new();
+    for record in history_outages.iter().rev() {
+        for action in incident_actions(record, jira_details) {
+            if !action.done && listed.insert(action.key.clone()) {
+                carried_over.push((record, action));
+            }
+        }
+    }
+
+    ActionsReport { incidents, carried_over }
+}
+
+pub fn incident_label(record: &OutageRecord) -> String {
+    let date = parse_date(&record.date)
+        .map(|d| d.format("%B %d").to_string())
+        .unwrap_or_else(|| record.date.clone());
+    format!("{} ({})", record.service.trim(), date)
+}
+
+/// Lists the follow-up actions for this week's incidents, then the actions from earlier incidents
+/// that are still open. Returns `None` when there is nothing to report.
+pub fn format_actions_section(report: &ActionsReport) -> Option<String> {
+    if report.is_empty() {
+        return None;
+    }
+
+    let mut section = String::from("Preventative actions:");
+    if report.incidents.is_empty() {
+        section.push_str("\nNone for this week's incidents");
+    }
+    for (record, actions) in &report.incidents {
+        if actions.is_empty() {
+            section.push_str(&format!("\n- {}: no follow-up linked in JIRA", incident_label(record)));
+        }
+        for action in actions {
+            section.push_str(&format!("\n- {}: {}", incident_label(record), action.describe()));
+        }
+    }
+    if !report.carried_over.is_empty() {
+        section.push_str("\n\nOpen actions carried over from previous weeks:");
+        for (record, action) in &report.carried_over {
+            section.push_str(&format!("\n- {}: {}", incident_label(record), action.describe()));
+        }
+    }
+    Some(section)
+}
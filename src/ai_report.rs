@@ -0,0 +1,490 @@
+use chrono::NaiveDate;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::llm::{call_llm, call_llm_batch, LlmConfig, LlmProvider, LlmResponse, ProviderKind};
+use crate::llm_cache::LlmCache;
+use crate::prompts::{PromptOutage, PromptTemplates};
+use crate::snapshot::{LlmExchange, LlmSnapshot};
+use crate::structured::{
+    incident_schema, link_recommendations, parse_incident_narrative, parse_summary, render_ai_report, summary_schema,
+    AiSummary, OutputMode, Recommendation,
+};
+use crate::verify::{verify_ai_report, Finding, GuardMode};
+use crate::{JiraIssue, OutageRecord};
+
+/// The AI-written report body, with the recommendations kept apart so they never reach the email.
+pub struct AiReport {
+    pub text: String,
+    /// Parsed narrative per incident, `None` where the standard entry is used
+    pub narratives: Vec<Option<String>>,
+    pub summary: Option<String>,
+    pub recommendations: Vec<Recommendation>,
+}
+
+fn exchange(prompt: &str, response: Result<LlmResponse, Box<dyn Error>>, stream: bool, cached: bool) -> LlmExchange {
+    match response {
+        Ok(response) => LlmExchange {
+            prompt: prompt.to_string(),
+            raw_response: Some(response.raw),
+            streamed: response.streamed,
+            error: None,
+            cached,
+            edited: None,
+        },
+        Err(e) => LlmExchange {
+            prompt: prompt.to_string(),
+            raw_response: None,
+            streamed: stream,
+            error: Some(e.to_string()),
+            cached,
+            edited: None,
+        },
+    }
+}
+
+/// Stores a fresh response once it has been parsed successfully, so malformed output is never reused.
+fn cache_exchange(cache: &LlmCache, key: &str, config: &LlmConfig, exchange: &LlmExchange) {
+    let Some(raw) = exchange.raw_response.as_ref().filter(|_| !exchange.cached) else {
+        return;
+    };
+    let response = LlmResponse {
+        raw: raw.clone(),
+        streamed: exchange.streamed,
+    };
+    if let Err(e) = cache.put(key, config, &response) {
+        warn!("Failed to cache LLM response: {}", e);
+    }
+}
+
+fn exchange_content(exchange: &LlmExchange, provider: &dyn LlmProvider) -> Result<String, Box<dyn Error>> {
+    match &exchange.raw_response {
+        Some(raw) => LlmResponse {
+            raw: raw.clone(),
+            streamed: exchange.streamed,
+        }
+        .content(provider),
+        None => Err(exchange.error.clone().unwrap_or_else(|| "No response".to_string()).into()),
+    }
+}
+
+fn exchange_narrative(exchange: &LlmExchange, provider: &dyn LlmProvider, mode: OutputMode) -> Result<String, Box<dyn Error>> {
+    if let Some(edited) = &exchange.edited {
+        return Ok(edited.clone());
+    }
+    exchange_content(exchange, provider).and_then(|content| parse_incident_narrative(mode, &content))
+}
+
+/// The narrative for the incident at `index`, without logging failures.
+pub fn incident_narrative(llm: &LlmSnapshot, index: usize) -> Result<String, Box<dyn Error>> {
+    let provider = ProviderKind::parse(&llm.provider)?.provider();
+    let exchange = llm.incidents.get(index).ok_or("No AI request was made for this incident")?;
+    exchange_narrative(exchange, provider.as_ref(), OutputMode::parse(&llm.output_mode)?)
+}
+
+/// The summary paragraph, without logging failures.
+pub fn report_summary(llm: &LlmSnapshot) -> Result<String, Box<dyn Error>> {
+    let provider = ProviderKind::parse(&llm.provider)?.provider();
+    let exchange = llm.summary.as_ref().ok_or("No summary was requested")?;
+    Ok(parse_exchange_summary(exchange, provider.as_ref(), OutputMode::parse(&llm.output_mode)?)?.summary)
+}
+
+/// Parses each incident's narrative, `None` where the request or its response failed.
+fn incident_narratives(exchanges: &[LlmExchange], provider: &dyn LlmProvider, mode: OutputMode) -> Vec<Option<String>> {
+    exchanges
+        .iter()
+        .enumerate()
+        .map(|(index, exchange)| match exchange_narrative(exchange, provider, mode) {
+            Ok(narrative) => Some(narrative),
+            Err(e) => {
+                warn!("Incident {}: using standard format, AI narrative failed: {}", index + 1, e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_exchange_summary(
+    exchange: &LlmExchange,
+    provider: &dyn LlmProvider,
+    mode: OutputMode,
+) -> Result<AiSummary, Box<dyn Error>> {
+    let parsed = exchange_content(exchange, provider).and_then(|content| parse_summary(mode, &content));
+    match &exchange.edited {
+        // An edited summary keeps the response's recommendations, if it has any
+        Some(edited) => Ok(AiSummary {
+            summary: edited.clone(),
+            recommendations: parsed.map(|summary| summary.recommendations).unwrap_or_default(),
+        }),
+        None => parsed,
+    }
+}
+
+fn exchange_summary(exchange: &LlmExchange, provider: &dyn LlmProvider, mode: OutputMode) -> Option<AiSummary> {
+    match parse_exchange_summary(exchange, provider, mode) {
+        Ok(summary) => Some(summary),
+        Err(e) => {
+            warn!("Leaving out the AI summary and recommendations: {}", e);
+            None
+        }
+    }
+}
+
+/// The model, response cache and prompts that narratives and summaries are generated with.
+pub struct NarrativeGenerator<'a> {
+    pub config: &'a LlmConfig,
+    pub cache: &'a LlmCache,
+    pub templates: &'a PromptTemplates,
+}
+
+/// Requests the summary and recommendations over the incidents' narratives, reusing a cached
+/// response to the same prompt. Returns the exchange and the system prompt it was sent with.
+async fn summary_exchange(
+    generator: &NarrativeGenerator<'_>,
+    prompt_outages: &[PromptOutage],
+    narratives: &[Option<String>],
+    week_number: u32,
+    week_start: &NaiveDate,
+    week_end: &NaiveDate,
+) -> Result<(LlmExchange, String), Box<dyn Error>> {
+    let NarrativeGenerator { config, cache, templates } = *generator;
+    let mode = config.output_mode;
+    let schema = match mode {
+        OutputMode::Structured => Some(summary_schema()),
+        OutputMode::Text => None,
+    };
+
+    let prompt = templates.render_summary(mode, prompt_outages, narratives, week_number, week_start, week_end)?;
+    let request = config.request(&prompt.system, &prompt.user, schema);
+    let key = LlmCache::key(config, &request);
+    let summary = match cache.get(&key) {
+        Some(response) => exchange(&prompt.user, Ok(response), config.stream, true),
+        None => {
+            let response = call_llm(config, &request).await;
+            let summary = exchange(&prompt.user, response, config.stream, false);
+            let parsed = exchange_content(&summary, config.provider.provider().as_ref())
+                .and_then(|content| parse_summary(mode, &content));
+            if parsed.is_ok() {
+                cache_exchange(cache, &key, config, &summary);
+            }
+            summary
+        }
+    };
+    Ok((summary, prompt.system))
+}
+
+/// Requests a narrative for every incident with bounded parallelism, then a summary and recommendations
+/// pass over the results. Failed requests are recorded rather than returned, so one bad incident
+/// doesn't lose the rest of the report. Responses to prompts that haven't changed are reused from the cache.
+pub async fn generate_llm_exchanges(
+    config: &LlmConfig,
+    cache: &LlmCache,
+    templates: &PromptTemplates,
+    prompt_outages: &[PromptOutage],
+    week_number: u32,
+    week_start: &NaiveDate,
+    week_end: &NaiveDate,
+) -> Result<LlmSnapshot, Box<dyn Error>> {
+    let mode = config.output_mode;
+    let provider = config.provider.provider();
+    let incident_schema = match mode {
+        OutputMode::Structured => Some(incident_schema()),
+        OutputMode::Text => None,
+    };
+
+    let mut system_prompt = String::new();
+    let mut prompts = Vec::new();
+    let mut requests = Vec::new();
+    for outage in prompt_outages {
+        let prompt = templates.render_incident(mode, outage, week_number, week_start, week_end)?;
+        requests.push(config.request(&prompt.system, &prompt.user, incident_schema.clone()));
+        system_prompt = prompt.system;
+        prompts.push(prompt.user);
+    }
+
+    let keys: Vec<String> = requests.iter().map(|request| LlmCache::key(config, request)).collect();
+    let mut responses: Vec<Option<Result<LlmResponse, Box<dyn Error>>>> =
+        keys.iter().map(|key| cache.get(key).map(Ok)).collect();
+    let cached: Vec<bool> = responses.iter().map(Option::is_some).collect();
+    let misses: Vec<usize> = (0..requests.len()).filter(|&index| !cached[index]).collect();
+
+    info!(
+        "Requesting {} incident narrative(s), up to {} at a time, {} reused from cache",
+        misses.len(),
+        config.concurrency,
+        requests.len() - misses.len()
+    );
+    let fresh = call_llm_batch(config, misses.iter().map(|&index| requests[index].clone()).collect()).await;
+    for (index, response) in misses.into_iter().zip(fresh) {
+        responses[index] = Some(response);
+    }
+
+    let incidents: Vec<LlmExchange> = prompts
+        .iter()
+        .zip(responses)
+        .zip(&cached)
+        .map(|((prompt, response), &cached)| {
+            let response = response.unwrap_or_else(|| Err("No response".into()));
+            exchange(prompt, response, config.stream, cached)
+        })
+        .collect();
+
+    // Failures are reported when the report is assembled
+    let mut narratives = Vec::new();
+    for (exchange, key) in incidents.iter().zip(&keys) {
+        let narrative = exchange_narrative(exchange, provider.as_ref(), mode).ok();
+        if narrative.is_some() {
+            cache_exchange(cache, key, config, exchange);
+        }
+        narratives.push(narrative);
+    }
+
+    let generator = NarrativeGenerator { config, cache, templates };
+    let summary = if narratives.iter().any(Option::is_some) || narratives.is_empty() {
+        let (summary, system) =
+            summary_exchange(&generator, prompt_outages, &narratives, week_number, week_start, week_end).await?;
+        system_prompt = system;
+        Some(summary)
+    } else {
+        None
+    };
+
+    Ok(LlmSnapshot {
+        provider: config.provider.to_string(),
+        url: config.url.clone(),
+        model: config.model.clone(),
+        prompt_template: Some(templates.id()),
+        system_prompt,
+        output_mode: mode.to_string(),
+        incidents,
+        summary,
+        // Set by the caller once the report has been checked
+        guard: GuardMode::Off.to_string(),
+        findings: Vec::new(),
+    })
+}
+
+/// Requests a new narrative for one incident, bypassing the cache. The response is only returned,
+/// and cached for later runs, if its narrative can be parsed.
+pub async fn regenerate_incident(
+    generator: &NarrativeGenerator<'_>,
+    prompt_outage: &PromptOutage,
+    week_number: u32,
+    week_start: &NaiveDate,
+    week_end: &NaiveDate,
+) -> Result<LlmExchange, Box<dyn Error>> {
+    let NarrativeGenerator { config, cache, templates } = *generator;
+    let mode = config.output_mode;
+    let schema = match mode {
+        OutputMode::Structured => Some(incident_schema()),
+        OutputMode::Text => None,
+    };
+    let prompt = templates.render_incident(mode, prompt_outage, week_number, week_start, week_end)?;
+    let request = config.request(&prompt.system, &prompt.user, schema);
+
+    let exchange = exchange(&prompt.user, call_llm(config, &request).await, config.stream, false);
+    exchange_narrative(&exchange, config.provider.provider().as_ref(), mode)?;
+    cache_exchange(cache, &LlmCache::key(config, &request), config, &exchange);
+    Ok(exchange)
+}
+
+/// Requests a new summary once incidents have been excluded or their narratives changed,
+/// so it doesn't describe the report as it was first generated.
+pub async fn refresh_summary(
+    generator: &NarrativeGenerator<'_>,
+    llm: &mut LlmSnapshot,
+    prompt_outages: &[PromptOutage],
+    week_number: u32,
+    week_start: &NaiveDate,
+    week_end: &NaiveDate,
+) -> Result<(), Box<dyn Error>> {
+    let narratives: Vec<Option<String>> = (0..llm.incidents.len())
+        .map(|index| incident_narrative(llm, index).ok())
+        .collect();
+    if !narratives.is_empty() && narratives.iter().all(Option::is_none) {
+        llm.summary = None;
+        return Ok(());
+    }
+
+    let (summary, system) =
+        summary_exchange(generator, prompt_outages, &narratives, week_number, week_start, week_end).await?;
+    llm.summary = Some(summary);
+    llm.system_prompt = system;
+    Ok(())
+}
+
+/// Builds the AI report body and recommendations from recorded exchanges, checked with the snapshot's guard mode.
+/// Fails only if no incident narrative could be used. Also returns what the check found.
+pub fn assemble_ai_report(
+    llm: &LlmSnapshot,
+    outages: &[OutageRecord],
+    prompt_outages: &[PromptOutage],
+    jira_details: &HashMap<String, JiraIssue>,
+    week_number: u32,
+    week_start: &NaiveDate,
+    week_end: &NaiveDate,
+) -> Result<(AiReport, Vec<Finding>), Box<dyn Error>> {
+    let provider = ProviderKind::parse(&llm.provider)?.provider();
+    let mode = OutputMode::parse(&llm.output_mode)?;
+
+    if llm.incidents.len() != outages.len() {
+        return Err(format!("Expected {} incident responses, found {}", outages.len(), llm.incidents.len()).into());
+    }
+
+    let narratives = incident_narratives(&llm.incidents, provider.as_ref(), mode);
+    if !narratives.is_empty() && narratives.iter().all(Option::is_none) {
+        return Err("No incident narrative could be generated".into());
+    }
+    let (summary, recommendations) = match llm
+        .summary
+        .as_ref()
+        .and_then(|exchange| exchange_summary(exchange, provider.as_ref(), mode))
+    {
+        Some(summary) => {
+            let text = Some(summary.summary.trim().to_string()).filter(|text| !text.is_empty());
+            (text, summary.recommendations)
+        }
+        None => (None, Vec::new()),
+    };
+
+    let verification = verify_ai_report(
+        narratives,
+        summary,
+        outages,
+        prompt_outages,
+        jira_details,
+        &llm.prompt_text(),
+        GuardMode::parse(&llm.guard)?,
+    );
+    let text = render_ai_report(
+        outages,
+        prompt_outages,
+        &verification.narratives,
+        verification.summary.as_deref(),
+        week_number,
+        week_start,
+        week_end,
+    );
+
+    let ai_report = AiReport {
+        text,
+        narratives: verification.narratives,
+        summary: verification.summary,
+        recommendations: link_recommendations(recommendations, outages),
+    };
+    Ok((ai_report, verification.findings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::{outage, ReportFixture};
+    use serde_json::json;
+
+    /// A recorded OpenAI-compatible response whose content is `content`.
+    fn response(content: String) -> LlmExchange {
+        exchange(
+            "prompt",
+            Ok(LlmResponse {
+                raw: json!({ "choices": [{ "message": { "content": content } }] }).to_string(),
+                streamed: false,
+            }),
+            false,
+            false,
+        )
+    }
+
+    fn llm_snapshot(narratives: &[&str]) -> LlmSnapshot {
+        LlmSnapshot {
+            provider: "openai".to_string(),
+            url: "http://127.0.0.1:1234/v1/chat/completions".to_string(),
+            model: "local-model".to_string(),
+            prompt_template: None,
+            system_prompt: String::new(),
+            output_mode: "structured".to_string(),
+            incidents: narratives.iter().map(|narrative| response(json!({ "narrative": narrative }).to_string())).collect(),
+            summary: Some(response(json!({ "summary": "A quiet week.", "recommendations": [] }).to_string())),
+            guard: GuardMode::Off.to_string(),
+            findings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn assembles_one_narrative_per_incident() {
+        let fixture = ReportFixture::new(vec![outage(1, "Nginx failed"), outage(2, "Disk filled up")]);
+        let report = fixture.report();
+        let llm = llm_snapshot(&["Nginx was restarted.", "The disk was cleaned up."]);
+
+        let (ai_report, findings) = assemble_ai_report(
+            &llm,
+            &fixture.outages,
+            &fixture.prompt_outages,
+            &fixture.jira_details,
+            41,
+            &report.week_start,
+            &report.week_end,
+        )
+        .unwrap();
+        assert!(findings.is_empty());
+        assert_eq!(
+            ai_report.narratives,
+            vec![Some("Nginx was restarted.".to_string()), Some("The disk was cleaned up.".to_string())]
+        );
+        assert_eq!(ai_report.summary.as_deref(), Some("A quiet week."));
+        assert!(ai_report.text.starts_with("Week 41 (October 11 - October 17)\nAll times UTC\n\nA quiet week.\n\n"));
+    }
+
+    #[test]
+    fn rejects_a_wrong_number_of_incident_responses() {
+        let fixture = ReportFixture::new(vec![outage(1, "Nginx failed"), outage(2, "Disk filled up")]);
+        let report = fixture.report();
+        let llm = llm_snapshot(&["Nginx was restarted."]);
+
+        let error = assemble_ai_report(
+            &llm,
+            &fixture.outages,
+            &fixture.prompt_outages,
+            &fixture.jira_details,
+            41,
+            &report.week_start,
+            &report.week_end,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.to_string(), "Expected 2 incident responses, found 1");
+    }
+
+    #[test]
+    fn excluded_incidents_leave_the_rest_aligned() {
+        let mut fixture = ReportFixture::new(vec![
+            outage(1, "Nginx failed"),
+            outage(2, "Disk filled up"),
+            outage(3, "Certificate expired"),
+        ]);
+        let mut llm =
+            llm_snapshot(&["Nginx was restarted.", "The disk was cleaned up.", "The certificate was renewed."]);
+
+        crate::keep_included(&mut fixture.outages, &mut fixture.prompt_outages, Some(&mut llm), &[true, false, true]);
+        let report = fixture.report();
+        let (ai_report, _) = assemble_ai_report(
+            &llm,
+            &fixture.outages,
+            &fixture.prompt_outages,
+            &fixture.jira_details,
+            41,
+            &report.week_start,
+            &report.week_end,
+        )
+        .unwrap();
+
+        let causes: Vec<&str> = fixture.outages.iter().map(|record| record.cause.as_str()).collect();
+        assert_eq!(causes, vec!["Nginx failed", "Certificate expired"]);
+        let prompts: Vec<&str> = fixture.prompt_outages.iter().map(|prompt| prompt.cause.as_str()).collect();
+        assert_eq!(prompts, causes);
+        assert_eq!(
+            ai_report.narratives,
+            vec![Some("Nginx was restarted.".to_string()), Some("The certificate was renewed.".to_string())]
+        );
+    }
+}
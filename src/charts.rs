@@ -0,0 +1,655 @@
+use chrono::{Datelike, Duration};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::metrics::{downtime_by_service, format_minutes};
+use crate::report::Report;
+
+/// Weeks in the severity history, including the report week.
+pub const SEVERITY_WEEKS: usize = 12;
+/// Services shown in the downtime chart; the rest are summed up in a note.
+pub const MAX_DOWNTIME_BARS: usize = 10;
+
+const WIDTH: f64 = 640.0;
+const FONT: &str = "Helvetica, Arial, sans-serif";
+const TEXT: &str = "#172b4d";
+const MUTED: &str = "#5e6c84";
+const GRID: &str = "#dfe1e6";
+const BAR: &str = "#2e86c1";
+
+/// Severity colours shared by every chart and the PDF, matching the HTML email's badges.
+pub fn severity_color(severity: &str) -> &'static str {
+    match severity.trim() {
+        "S1" => "#c0392b",
+        "S2" => "#e67e22",
+        "S3" => "#d4ac0d",
+        "S4" => "#2e86c1",
+        _ => "#7f8c8d",
+    }
+}
+
+/// An incident on the timeline, positioned as fractions of the report week.
+pub struct TimelineBar {
+    pub start: f64,
+    pub end: f64,
+    pub severity: String,
+    pub estimated: bool,
+    /// e.g. "S1 October 14 10:05 - 11:08 (1h 3min)"
+    pub label: String,
+}
+
+/// One service's row on the timeline.
+pub struct TimelineRow {
+    pub service: String,
+    pub bars: Vec<TimelineBar>,
+}
+
+/// Incidents per service across the week, in order of each service's first incident.
+/// Incidents without a usable date are left out.
+pub fn timeline_rows(report: &Report) -> Vec<TimelineRow> {
+    let week_start = report.week_start.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    let week_minutes = ((report.week_end - report.week_start).num_days() + 1) as f64 * 24.0 * 60.0;
+    let fraction =
+        |time: chrono::NaiveDateTime| ((time - week_start).num_minutes() as f64 / week_minutes).clamp(0.0, 1.0);
+
+    let mut rows: Vec<TimelineRow> = Vec::new();
+    for incident in &report.incidents {
+        let Some(window) = incident.window else {
+            continue;
+        };
+        let service = incident.record.service.trim();
+        let bar = TimelineBar {
+            start: fraction(window.start),
+            end: fraction(window.end),
+            severity: incident.record.severity.trim().to_string(),
+            estimated: window.estimated,
+            label: format!(
+                "{} {} {} - {} ({}){}",
+                incident.record.severity.trim(),
+                window.start.format("%B %d"),
+                window.start.format("%H:%M"),
+                window.end.format("%H:%M"),
+                format_minutes((window.end - window.start).num_minutes() as f64),
+                if window.estimated { ", estimated" } else { "" }
+            ),
+        };
+        match rows.iter_mut().find(|row| row.service == service) {
+            Some(row) => row.bars.push(bar),
+            None => rows.push(TimelineRow {
+                service: service.to_string(),
+                bars: vec![bar],
+            }),
+        }
+    }
+    rows
+}
+
+/// Incident counts by severity for one week of the history.
+pub struct SeverityWeek {
+    /// e.g. "W41"
+    pub label: String,
+    pub counts: Vec<(String, usize)>,
+}
+
+/// Incidents by severity for up to `SEVERITY_WEEKS` weeks ending with the report week, oldest first,
+/// along with every severity that appears. Weeks before the CSV's first record aren't included.
+pub fn severity_history(report: &Report) -> (Vec<String>, Vec<SeverityWeek>) {
+    let weeks: Vec<_> = std::iter::once(report.kpis)
+        .chain(report.trends.previous_weeks.iter())
+        .take(SEVERITY_WEEKS)
+        .enumerate()
+        .map(|(weeks_back, metrics)| {
+            let start = report.week_start - Duration::weeks(weeks_back as i64);
+            SeverityWeek {
+                label: format!("W{}", start.iso_week().week()),
+                counts: metrics.by_severity.iter().map(|(severity, count)| (severity.clone(), *count)).collect(),
+            }
+        })
+        .collect();
+
+    let severities: BTreeSet<String> = weeks
+        .iter()
+        .flat_map(|week| week.counts.iter().map(|(severity, _)| severity.clone()))
+        .collect();
+    (severities.into_iter().collect(), weeks.into_iter().rev().collect())
+}
+
+/// Escapes text for SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn text(x: f64, y: f64, anchor: &str, size: f64, fill: &str, content: &str) -> String {
+    format!(
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>",
+        x,
+        y,
+        anchor,
+        size,
+        fill,
+        escape(content)
+    )
+}
+
+fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> String {
+    format!(
+        "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"1\"/>",
+        x1, y1, x2, y2, GRID
+    )
+}
+
+/// Truncates a label to roughly fit `width` pixels at 11px.
+fn fit(label: &str, width: f64) -> String {
+    let max_chars = (width / 6.2) as usize;
+    if label.chars().count() <= max_chars {
+        return label.to_string();
+    }
+    let truncated: String = label.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+fn svg(height: f64, title: &str, body: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"100%\" style=\"max-width:{w}px\" font-family=\"{font}\" role=\"img\" aria-label=\"{title}\"><title>{title}</title>{body}</svg>",
+        w = WIDTH,
+        h = height,
+        font = FONT,
+        title = escape(title),
+        body = body
+    )
+}
+
+/// Gantt-style timeline of the week's incidents, one row per service.
+/// Bars with estimated times are drawn lighter.
+pub fn timeline_svg(report: &Report) -> Option<String> {
+    const LABEL_WIDTH: f64 = 150.0;
+    const TOP: f64 = 24.0;
+    const ROW: f64 = 22.0;
+
+    let rows = timeline_rows(report);
+    if rows.is_empty() {
+        return None;
+    }
+    let plot_width = WIDTH - LABEL_WIDTH - 10.0;
+    let days = (report.week_end - report.week_start).num_days() + 1;
+    let height = TOP + rows.len() as f64 * ROW + 22.0;
+    let mut body = String::new();
+
+    for day in 0..=days {
+        let x = LABEL_WIDTH + plot_width * day as f64 / days as f64;
+        body.push_str(&line(x, TOP - 4.0, x, TOP + rows.len() as f64 * ROW));
+        if day < days {
+            let date = report.week_start + Duration::days(day);
+            let center = x + plot_width / days as f64 / 2.0;
+            let label = date.format("%a %d").to_string();
+            body.push_str(&text(center, TOP - 9.0, "middle", 11.0, MUTED, &label));
+        }
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let y = TOP + index as f64 * ROW;
+        let label = fit(&row.service, LABEL_WIDTH - 12.0);
+        body.push_str(&text(LABEL_WIDTH - 8.0, y + 15.0, "end", 11.0, TEXT, &label));
+        for bar in &row.bars {
+            let x = LABEL_WIDTH + plot_width * bar.start;
+            // Short incidents still get a visible sliver
+            let width = (plot_width * (bar.end - bar.start)).max(3.0);
+            body.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"14\" rx=\"2\" fill=\"{}\" fill-opacity=\"{}\"><title>{}</title></rect>",
+                x,
+                y + 4.0,
+                width,
+                severity_color(&bar.severity),
+                if bar.estimated { "0.5" } else { "1" },
+                escape(&format!("{}: {}", row.service, bar.label))
+            ));
+        }
+    }
+
+    body.push_str(&text(
+        LABEL_WIDTH,
+        height - 6.0,
+        "start",
+        10.0,
+        MUTED,
+        "UTC. Lighter bars have estimated times.",
+    ));
+    Some(svg(height, "Incident timeline", &body))
+}
+
+/// Horizontal bars of downtime minutes per service, most first.
+pub fn downtime_svg(report: &Report) -> Option<String> {
+    const LABEL_WIDTH: f64 = 170.0;
+    const VALUE_WIDTH: f64 = 70.0;
+    const ROW: f64 = 22.0;
+
+    let downtime = downtime_by_service(report.incidents.iter().map(|incident| incident.record));
+    if downtime.is_empty() {
+        return None;
+    }
+    let shown = &downtime[..downtime.len().min(MAX_DOWNTIME_BARS)];
+    let longest = shown.iter().map(|(_, minutes)| *minutes).max().unwrap_or(0).max(1) as f64;
+    let plot_width = WIDTH - LABEL_WIDTH - VALUE_WIDTH;
+    let mut height = 8.0 + shown.len() as f64 * ROW;
+    let mut body = String::new();
+
+    for (index, (service, minutes)) in shown.iter().enumerate() {
+        let y = 4.0 + index as f64 * ROW;
+        let width = (plot_width * *minutes as f64 / longest).max(2.0);
+        let label = fit(service, LABEL_WIDTH - 12.0);
+        body.push_str(&text(LABEL_WIDTH - 8.0, y + 14.0, "end", 11.0, TEXT, &label));
+        body.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"14\" rx=\"2\" fill=\"{}\"/>",
+            LABEL_WIDTH,
+            y + 3.0,
+            width,
+            BAR
+        ));
+        let value = format_minutes(*minutes as f64);
+        body.push_str(&text(LABEL_WIDTH + width + 6.0, y + 14.0, "start", 11.0, MUTED, &value));
+    }
+    if downtime.len() > shown.len() {
+        let rest: i32 = downtime[shown.len()..].iter().map(|(_, minutes)| minutes).sum();
+        height += 16.0;
+        body.push_str(&text(
+            LABEL_WIDTH,
+            height - 6.0,
+            "start",
+            10.0,
+            MUTED,
+            &format!("and {} more services, {} in total", downtime.len() - shown.len(), format_minutes(rest as f64)),
+        ));
+    }
+    Some(svg(height, "Downtime by service", &body))
+}
+
+/// Stacked bars of incidents by severity for each of the last `SEVERITY_WEEKS` weeks.
+pub fn severity_svg(report: &Report) -> Option<String> {
+    const LEFT: f64 = 36.0;
+    const TOP: f64 = 30.0;
+    const PLOT_HEIGHT: f64 = 150.0;
+
+    let (severities, weeks) = severity_history(report);
+    if weeks.is_empty() {
+        return None;
+    }
+    let most = weeks
+        .iter()
+        .map(|week| week.counts.iter().map(|(_, count)| count).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let step = most.div_ceil(4).max(1);
+    let top_value = step * most.div_ceil(step);
+    let plot_width = WIDTH - LEFT - 10.0;
+    // Always room for the full history, so bars keep their width while it builds up
+    let slot = plot_width / SEVERITY_WEEKS as f64;
+    let first_slot = SEVERITY_WEEKS - weeks.len();
+    let scale = PLOT_HEIGHT / top_value as f64;
+    let bottom = TOP + PLOT_HEIGHT;
+    let mut body = String::new();
+
+    // Legend
+    let mut x = LEFT;
+    for severity in &severities {
+        body.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"6\" width=\"10\" height=\"10\" fill=\"{}\"/>",
+            x,
+            severity_color(severity)
+        ));
+        body.push_str(&text(x + 14.0, 15.0, "start", 11.0, TEXT, severity));
+        x += 24.0 + severity.chars().count() as f64 * 7.0;
+    }
+
+    for value in (0..=top_value).step_by(step) {
+        let y = bottom - value as f64 * scale;
+        body.push_str(&line(LEFT, y, WIDTH - 10.0, y));
+        body.push_str(&text(LEFT - 6.0, y + 4.0, "end", 10.0, MUTED, &value.to_string()));
+    }
+
+    for (index, week) in weeks.iter().enumerate() {
+        let x = LEFT + slot * (first_slot + index) as f64 + slot * 0.2;
+        let mut y = bottom;
+        for severity in &severities {
+            let count = week
+                .counts
+                .iter()
+                .find(|(name, _)| name == severity)
+                .map(|(_, count)| *count)
+                .unwrap_or(0);
+            if count == 0 {
+                continue;
+            }
+            let height = count as f64 * scale;
+            y -= height;
+            body.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}</title></rect>",
+                x,
+                y,
+                slot * 0.6,
+                height,
+                severity_color(severity),
+                escape(&format!("{}: {} {}", week.label, count, severity))
+            ));
+        }
+        body.push_str(&text(x + slot * 0.3, bottom + 14.0, "middle", 10.0, MUTED, &week.label));
+    }
+
+    Some(svg(bottom + 22.0, "Incidents by severity", &body))
+}
+
+/// A rendered chart, as linked from the Markdown report.
+#[derive(Debug, Serialize)]
+pub struct Chart {
+    /// Used in file names, e.g. `timeline`
+    pub name: &'static str,
+    pub title: &'static str,
+    pub svg: String,
+}
+
+/// Every chart that has data this week.
+pub fn report_charts(report: &Report) -> Vec<Chart> {
+    [
+        ("timeline", "Incident Timeline", timeline_svg(report)),
+        ("downtime", "Downtime by Service", downtime_svg(report)),
+        ("severity", "Incidents by Severity", severity_svg(report)),
+    ]
+    .into_iter()
+    .filter_map(|(name, title, svg)| svg.map(|svg| Chart { name, title, svg }))
+    .collect()
+}
+
+/// A stretch of a bar in an email chart, `width` percent of the bar's full length. Gaps have no colour.
+#[derive(Debug, Serialize)]
+pub struct BarSegment {
+    pub width: f64,
+    pub color: Option<String>,
+    pub title: String,
+}
+
+/// One labelled bar in an email chart.
+#[derive(Debug, Serialize)]
+pub struct BarRow {
+    pub label: String,
+    pub segments: Vec<BarSegment>,
+    pub value: String,
+}
+
+/// A chart as rows of coloured table cells, which Outlook for Windows and Gmail display, unlike inline SVG.
+#[derive(Debug, Serialize)]
+pub struct TableChart {
+    pub name: &'static str,
+    pub title: &'static str,
+    /// Labels spread evenly above the bars, e.g. the days of the week
+    pub scale: Vec<String>,
+    pub rows: Vec<BarRow>,
+    /// Severities and their colours
+    pub legend: Vec<(String, &'static str)>,
+    pub note: Option<String>,
+}
+
+/// Mixes a `#rrggbb` colour halfway to white, for bars with estimated times.
+fn lighten(color: &str) -> String {
+    let channel = |index: usize| u8::from_str_radix(&color[index..index + 2], 16).unwrap_or(0);
+    let mix = |value: u8| (value as u16 + 255) / 2;
+    format!("#{:02x}{:02x}{:02x}", mix(channel(1)), mix(channel(3)), mix(channel(5)))
+}
+
+fn bar(width: f64, color: Option<String>, title: String) -> BarSegment {
+    BarSegment {
+        width: (width * 10.0).round() / 10.0,
+        color,
+        title,
+    }
+}
+
+fn timeline_table(report: &Report) -> Option<TableChart> {
+    let rows = timeline_rows(report);
+    if rows.is_empty() {
+        return None;
+    }
+    let days = (report.week_end - report.week_start).num_days() + 1;
+
+    // A table row can't draw bars on top of each other, so an incident that overlaps an earlier one of the
+    // same service goes on an extra row below it, at its real time
+    let mut lanes: Vec<BarRow> = Vec::new();
+    for row in rows {
+        let mut bars = row.bars;
+        bars.sort_by(|a, b| a.start.total_cmp(&b.start));
+        let mut service_lanes: Vec<(f64, Vec<BarSegment>)> = Vec::new();
+        for incident in bars {
+            // Short incidents still get a visible sliver
+            let start = incident.start.min(0.99);
+            let end = incident.end.max(start + 0.01).min(1.0);
+            let lane = match service_lanes.iter().position(|(position, _)| *position <= start) {
+                Some(lane) => lane,
+                None => {
+                    service_lanes.push((0.0, Vec::new()));
+                    service_lanes.len() - 1
+                }
+            };
+            let (position, segments) = &mut service_lanes[lane];
+            if start > *position {
+                segments.push(bar((start - *position) * 100.0, None, String::new()));
+            }
+            let color = severity_color(&incident.severity);
+            let color = if incident.estimated { lighten(color) } else { color.to_string() };
+            segments.push(bar((end - start) * 100.0, Some(color), incident.label));
+            *position = end;
+        }
+        for (index, (position, mut segments)) in service_lanes.into_iter().enumerate() {
+            if position < 1.0 {
+                segments.push(bar((1.0 - position) * 100.0, None, String::new()));
+            }
+            lanes.push(BarRow {
+                label: if index == 0 { row.service.clone() } else { String::new() },
+                segments,
+                value: String::new(),
+            });
+        }
+    }
+
+    Some(TableChart {
+        name: "timeline",
+        title: "Incident Timeline",
+        scale: (0..days)
+            .map(|day| (report.week_start + Duration::days(day)).format("%a %d").to_string())
+            .collect(),
+        rows: lanes,
+        legend: Vec::new(),
+        note: Some("UTC. Lighter bars have estimated times.".to_string()),
+    })
+}
+
+fn downtime_table(report: &Report) -> Option<TableChart> {
+    let downtime = downtime_by_service(report.incidents.iter().map(|incident| incident.record));
+    if downtime.is_empty() {
+        return None;
+    }
+    let shown = &downtime[..downtime.len().min(MAX_DOWNTIME_BARS)];
+    let longest = shown.iter().map(|(_, minutes)| *minutes).max().unwrap_or(0).max(1) as f64;
+
+    let rows = shown
+        .iter()
+        .map(|(service, minutes)| {
+            let width = (100.0 * *minutes as f64 / longest).max(1.0);
+            let value = format_minutes(*minutes as f64);
+            let mut segments = vec![bar(width, Some(BAR.to_string()), value.clone())];
+            if width < 100.0 {
+                segments.push(bar(100.0 - width, None, String::new()));
+            }
+            BarRow {
+                label: service.clone(),
+                segments,
+                value,
+            }
+        })
+        .collect();
+    let note = (downtime.len() > shown.len()).then(|| {
+        let rest: i32 = downtime[shown.len()..].iter().map(|(_, minutes)| minutes).sum();
+        format!("and {} more services, {} in total", downtime.len() - shown.len(), format_minutes(rest as f64))
+    });
+
+    Some(TableChart {
+        name: "downtime",
+        title: "Downtime by Service",
+        scale: Vec::new(),
+        rows,
+        legend: Vec::new(),
+        note,
+    })
+}
+
+fn severity_table(report: &Report) -> Option<TableChart> {
+    let (severities, weeks) = severity_history(report);
+    if weeks.is_empty() {
+        return None;
+    }
+    let most = weeks
+        .iter()
+        .map(|week| week.counts.iter().map(|(_, count)| count).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+
+    let rows = weeks
+        .into_iter()
+        .map(|week| {
+            let total: usize = week.counts.iter().map(|(_, count)| count).sum();
+            let mut segments: Vec<BarSegment> = week
+                .counts
+                .iter()
+                .map(|(severity, count)| {
+                    let color = severity_color(severity).to_string();
+                    bar(100.0 * *count as f64 / most, Some(color), format!("{}: {}", severity, count))
+                })
+                .collect();
+            if (total as f64) < most {
+                segments.push(bar(100.0 * (most - total as f64) / most, None, String::new()));
+            }
+            BarRow {
+                label: week.label,
+                segments,
+                value: total.to_string(),
+            }
+        })
+        .collect();
+
+    Some(TableChart {
+        name: "severity",
+        title: "Incidents by Severity",
+        scale: Vec::new(),
+        rows,
+        legend: severities
+            .into_iter()
+            .map(|severity| {
+                let color = severity_color(&severity);
+                (severity, color)
+            })
+            .collect(),
+        note: None,
+    })
+}
+
+/// The charts that have data this week, as table rows for the HTML email.
+pub fn email_charts(report: &Report) -> Vec<TableChart> {
+    [timeline_table(report), downtime_table(report), severity_table(report)]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Writes the charts to `CHARTS_DIR` (default `charts`) as `week-<year>-<week>-<name>.svg`,
+/// returning each chart's title and path.
+pub fn write_charts(report: &Report) -> Result<Vec<(&'static str, PathBuf)>, Box<dyn Error>> {
+    let dir = PathBuf::from(env::var("CHARTS_DIR").unwrap_or_else(|_| "charts".to_string()));
+    fs::create_dir_all(&dir)?;
+
+    let mut written = Vec::new();
+    for chart in report_charts(report) {
+        let path = dir.join(format!("{}-{}.svg", report.file_stem(), chart.name));
+        fs::write(&path, &chart.svg).map_err(|e| format!("Failed to write chart {}: {}", path.display(), e))?;
+        written.push((chart.title, path));
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::render_html;
+    use crate::report::fixture::{outage, ReportFixture};
+    use crate::OutageRecord;
+
+    #[test]
+    fn email_chart_bars_fill_the_row() {
+        let fixture = ReportFixture::new((1..=3).map(|ticket| outage(ticket, "Nginx failed")).collect());
+        let charts = email_charts(&fixture.report());
+        let names: Vec<&str> = charts.iter().map(|chart| chart.name).collect();
+        assert_eq!(names, vec!["timeline", "downtime", "severity"]);
+
+        for chart in &charts {
+            for row in &chart.rows {
+                let total: f64 = row.segments.iter().map(|segment| segment.width).sum();
+                assert!((total - 100.0).abs() < 0.5, "{} {} adds up to {}", chart.name, row.label, total);
+            }
+        }
+        // Incidents at the same time are stacked on rows of their own, each at its real time
+        let timeline = &charts[0].rows;
+        let labels: Vec<&str> = timeline.iter().map(|row| row.label.as_str()).collect();
+        assert_eq!(labels, vec!["Sales-I US", "", ""]);
+        for row in timeline {
+            let widths: Vec<f64> = row.segments.iter().map(|segment| segment.width).collect();
+            assert_eq!(widths, timeline[0].segments.iter().map(|segment| segment.width).collect::<Vec<_>>());
+            assert_eq!(row.segments.iter().filter(|segment| segment.color.is_some()).count(), 1);
+        }
+        assert_eq!(charts[1].rows[0].value, "45min");
+    }
+
+    #[test]
+    fn timeline_only_stacks_overlapping_incidents() {
+        let at = |ticket: usize, date: &str, duration: &str| OutageRecord {
+            date: date.to_string(),
+            duration: duration.to_string(),
+            ..outage(ticket, "Nginx failed")
+        };
+        let fixture = ReportFixture::new(vec![
+            at(1, "12/Oct/26", "600"),
+            at(2, "12/Oct/26", "15"),
+            at(3, "14/Oct/26", "15"),
+            OutageRecord { service: "Market".to_string(), ..at(4, "15/Oct/26", "15") },
+        ]);
+        let report = fixture.report();
+        let table = timeline_table(&report).unwrap();
+
+        let rows: Vec<(&str, usize)> = table
+            .rows
+            .iter()
+            .map(|row| (row.label.as_str(), row.segments.iter().filter(|segment| segment.color.is_some()).count()))
+            .collect();
+        assert_eq!(rows, vec![("Sales-I US", 2), ("", 1), ("Market", 1)]);
+
+        // The stacked incident starts where the timeline puts it, not after the one it overlaps
+        let windows: Vec<_> = report.incidents.iter().map(|incident| incident.window.unwrap()).collect();
+        let week_start = report.week_start.and_hms_opt(0, 0, 0).unwrap();
+        let expected = (windows[1].start - week_start).num_minutes() as f64 / (7.0 * 24.0 * 60.0) * 100.0;
+        assert!((table.rows[1].segments[0].width - expected).abs() < 0.1);
+        assert!(windows[1].start < windows[0].end);
+    }
+
+    #[test]
+    fn html_email_draws_charts_without_svg() {
+        let fixture = ReportFixture::new(vec![outage(1, "Nginx failed")]);
+        let html = render_html(&fixture.report()).unwrap();
+        assert!(!html.contains("<svg"));
+        assert!(html.contains("Downtime by Service"));
+        assert!(html.contains(&format!("bgcolor=\"{}\"", BAR)));
+    }
+}
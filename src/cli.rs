@@ -0,0 +1,216 @@
+use clap::{ArgGroup, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Generates the weekly stability report from outages.csv, JIRA and an optional local LLM.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Only use cached JIRA data, never contact JIRA
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Write every JIRA payload used by this run to DIR as cache entries, for use as test fixtures
+    #[arg(long, value_name = "DIR")]
+    pub record_fixtures: Option<PathBuf>,
+
+    /// Ignore cached LLM responses and request new ones
+    #[arg(long)]
+    pub refresh_ai: bool,
+
+    /// Review the incidents in a terminal dashboard, to exclude them, edit or regenerate
+    /// their narratives, before the report is written
+    #[arg(long)]
+    pub dashboard: bool,
+
+    /// Email the report to EMAIL_TO, EMAIL_CC and EMAIL_BCC over SMTP
+    #[arg(long, global = true, group = "delivery")]
+    pub send: bool,
+
+    /// Post the report to SLACK_WEBHOOK_URL
+    #[arg(long, global = true, group = "delivery")]
+    pub slack: bool,
+
+    /// Post the report to TEAMS_WEBHOOK_URL
+    #[arg(long, global = true, group = "delivery")]
+    pub teams: bool,
+
+    /// Publish the report as a page under CONFLUENCE_PARENT_ID
+    #[arg(long, global = true, group = "delivery")]
+    pub confluence: bool,
+
+    /// Write outgoing messages to OUTBOX_DIR instead of delivering them
+    #[arg(long, global = true, requires = "delivery")]
+    pub dry_run: bool,
+
+    /// Deliver the approved draft again to destinations it was already delivered to
+    #[arg(long, global = true, requires = "delivery")]
+    pub redeliver: bool,
+
+    /// Format of the report printed to stdout
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text, global = true)]
+    pub format: ReportFormat,
+
+    /// Also write the report as a PDF to PATH
+    #[arg(long, value_name = "PATH", global = true)]
+    pub pdf: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Parses the command line. Use this rather than `Cli::parse`, which rejects combined delivery flags after a
+    /// subcommand.
+    pub fn parse_args() -> Self {
+        Self::try_parse_args_from(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    pub fn try_parse_args_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut matches = with_delivery_group(Cli::command()).try_get_matches_from(args)?;
+        Cli::from_arg_matches_mut(&mut matches)
+    }
+}
+
+/// The delivery flags are global, and clap gives every subcommand they propagate to its own "delivery" group, which
+/// only allows one of them unless the subcommand declares the group itself.
+fn with_delivery_group(command: clap::Command) -> clap::Command {
+    command.group(ArgGroup::new("delivery").multiple(true)).mut_subcommands(with_delivery_group)
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Rebuild a previous report from a saved snapshot bundle
+    Regenerate {
+        /// Path to the snapshot JSON file
+        #[arg(long, value_name = "FILE")]
+        from_snapshot: PathBuf,
+    },
+    /// Review, approve and deliver the report drafts saved by each run
+    Review {
+        /// Week to review as YEAR-WEEK, e.g. 2026-41, defaults to last week
+        #[arg(long, value_name = "YEAR-WEEK", value_parser = parse_week)]
+        week: Option<String>,
+
+        #[command(subcommand)]
+        action: ReviewCommand,
+    },
+    /// Print a reliability report for a calendar month, with --pdf to also write it as a PDF
+    Monthly {
+        /// Month to report as YEAR-MONTH, e.g. 2026-10, defaults to last month
+        #[arg(long, value_name = "YEAR-MONTH", value_parser = parse_month)]
+        month: Option<String>,
+    },
+    /// List or print the reports stored in the history database
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// List the stored reports, newest first
+    List {
+        /// Only list reports for this week, as YEAR-WEEK
+        #[arg(long, value_name = "YEAR-WEEK", value_parser = parse_week)]
+        week: Option<String>,
+    },
+    /// Print a stored report exactly as it was generated
+    Show {
+        /// Report id, as listed by `history list`
+        id: i64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReviewCommand {
+    /// Show the latest draft's state and the review history
+    Status,
+    /// Print the latest draft in --format
+    Show,
+    /// Edit the latest draft's summary and incidents in $VISUAL or $EDITOR, saving a new draft
+    Edit,
+    /// Show what changed in the latest draft
+    Diff {
+        /// Draft to compare with, defaults to the previous one
+        #[arg(long, value_name = "DRAFT")]
+        since: Option<u32>,
+    },
+    /// Mark the latest draft as ready for review
+    Submit,
+    /// Approve the draft in review, so it can be delivered
+    Approve,
+    /// Send the draft in review back for changes
+    Reject {
+        /// What needs to change
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Deliver the approved draft with --send, --slack, --teams and --confluence
+    Deliver,
+}
+
+/// Normalizes a `YEAR-WEEK` argument, e.g. `2026-8` to `2026-08`.
+fn parse_week(value: &str) -> Result<String, String> {
+    let invalid = || format!("expected YEAR-WEEK, e.g. 2026-41, found {}", value);
+    let (year, week) = value.split_once('-').ok_or_else(invalid)?;
+    let year: u32 = year.parse().map_err(|_| invalid())?;
+    let week: u32 = week.parse().map_err(|_| invalid())?;
+    if !(1..=53).contains(&week) {
+        return Err(invalid());
+    }
+    Ok(format!("{}-{:02}", year, week))
+}
+
+/// Normalizes a `YEAR-MONTH` argument, e.g. `2026-8` to `2026-08`.
+fn parse_month(value: &str) -> Result<String, String> {
+    let invalid = || format!("expected YEAR-MONTH, e.g. 2026-10, found {}", value);
+    let (year, month) = value.split_once('-').ok_or_else(invalid)?;
+    let year: u32 = year.parse().map_err(|_| invalid())?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    if !(1..=12).contains(&month) {
+        return Err(invalid());
+    }
+    Ok(format!("{}-{:02}", year, month))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Plain text, as stored in history and snapshots
+    Text,
+    Markdown,
+    /// Email-ready HTML with inline styles
+    Html,
+    /// Machine-readable JSON, see schemas/report.schema.json
+    Json,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        with_delivery_group(Cli::command()).debug_assert();
+    }
+
+    #[test]
+    fn dry_run_needs_a_destination() {
+        assert!(Cli::try_parse_args_from(["weekly-status-report", "--dry-run"]).is_err());
+        assert!(Cli::try_parse_args_from(["weekly-status-report", "review", "deliver", "--dry-run"]).is_err());
+        assert!(Cli::try_parse_args_from(["weekly-status-report", "review", "deliver", "--redeliver"]).is_err());
+
+        let cli = Cli::try_parse_args_from(["weekly-status-report", "--send", "--slack", "--dry-run"]).unwrap();
+        assert!(cli.send && cli.slack && cli.dry_run);
+        let cli = Cli::try_parse_args_from(["weekly-status-report", "review", "deliver", "--teams", "--dry-run"]).unwrap();
+        assert!(cli.teams && cli.dry_run);
+        let args = ["weekly-status-report", "review", "deliver", "--send", "--slack", "--teams", "--confluence"];
+        let cli = Cli::try_parse_args_from(args).unwrap();
+        assert!(cli.send && cli.slack && cli.teams && cli.confluence);
+    }
+}
@@ -0,0 +1,399 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::info;
+use serde_json::{json, Value};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
+
+use crate::actions::{incident_label, PreventativeAction};
+use crate::email::render_subject;
+use crate::report::{issue_url, outbox_dir, Report, ReportIncident};
+use crate::OutageRecord;
+
+const DEFAULT_BASE_URL: &str = "https://sugarcrm.atlassian.net/wiki";
+const DEFAULT_TITLE: &str = "Weekly Stability Report – {year} Week {n}";
+const DEFAULT_LABEL: &str = "weekly-stability-report";
+
+/// Where and how to publish the report, read from the environment.
+#[derive(Debug, Clone)]
+pub struct ConfluenceConfig {
+    pub base_url: String,
+    pub space: String,
+    pub parent_id: String,
+    pub title: String,
+    pub label: String,
+    /// Application link ID of the JIRA server, for sites with more than one
+    pub jira_server_id: Option<String>,
+}
+
+fn required_var(name: &str) -> Result<String, Box<dyn Error>> {
+    env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| format!("{} must be set to publish the report to Confluence", name).into())
+}
+
+impl ConfluenceConfig {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        Ok(ConfluenceConfig {
+            base_url: env::var("CONFLUENCE_URL")
+                .unwrap_or_else(|_| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            space: required_var("CONFLUENCE_SPACE")?,
+            parent_id: required_var("CONFLUENCE_PARENT_ID")?,
+            title: env::var("CONFLUENCE_TITLE").unwrap_or_else(|_| DEFAULT_TITLE.to_string()),
+            label: env::var("CONFLUENCE_LABEL").unwrap_or_else(|_| DEFAULT_LABEL.to_string()),
+            jira_server_id: env::var("CONFLUENCE_JIRA_SERVER_ID").ok().filter(|id| !id.trim().is_empty()),
+        })
+    }
+}
+
+/// Escapes text for Confluence storage format, which is XHTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn link(text: &str, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("<a href=\"{}\">{}</a>", escape(url), escape(text)),
+        None => escape(text),
+    }
+}
+
+fn jira_macro(key: &str, server_id: Option<&str>) -> String {
+    let mut parameters = format!("<ac:parameter ac:name=\"key\">{}</ac:parameter>", escape(key));
+    if let Some(server_id) = server_id {
+        parameters.push_str(&format!("<ac:parameter ac:name=\"serverId\">{}</ac:parameter>", escape(server_id)));
+    }
+    format!("<ac:structured-macro ac:name=\"jira\">{}</ac:structured-macro>", parameters)
+}
+
+/// A page properties block with the week and its KPIs, so a page properties report
+/// on the parent page can tabulate every week.
+fn page_properties(report: &Report) -> String {
+    let mut rows = vec![
+        ("Week".to_string(), report.week_number.to_string()),
+        (
+            "Period".to_string(),
+            format!("{} - {}", report.week_start.format("%Y-%m-%d"), report.week_end.format("%Y-%m-%d")),
+        ),
+    ];
+    rows.extend(report.metrics.iter().map(|row| (row.label.to_string(), row.value.clone())));
+
+    let rows: String = rows
+        .iter()
+        .map(|(label, value)| format!("<tr><th>{}</th><td>{}</td></tr>", escape(label), escape(value)))
+        .collect();
+    format!(
+        "<ac:structured-macro ac:name=\"details\"><ac:rich-text-body><table><tbody>{}</tbody></table></ac:rich-text-body></ac:structured-macro>",
+        rows
+    )
+}
+
+fn trends(report: &Report) -> String {
+    let items: String = report
+        .metrics
+        .iter()
+        .filter_map(|row| {
+            let trend = row.trend.as_deref()?;
+            Some(format!("<li><strong>{}:</strong> {}</li>", escape(row.label), escape(trend)))
+        })
+        .collect();
+    if items.is_empty() {
+        return String::new();
+    }
+    format!("<h2>Trends</h2><ul>{}</ul>", items)
+}
+
+fn action_item(record: &OutageRecord, action: &PreventativeAction) -> String {
+    let mut item = format!(
+        "<li><strong>{}:</strong> {}",
+        escape(&incident_label(record)),
+        link(&action.key, issue_url(&record.ticket, &action.key).as_deref())
+    );
+    if let Some(summary) = &action.summary {
+        item.push_str(&format!(" {}", escape(summary.trim())));
+    }
+    item.push_str(&escape(&action.progress()));
+    item.push_str("</li>");
+    item
+}
+
+fn actions_section(report: &Report) -> String {
+    let actions = &report.actions;
+    if actions.is_empty() {
+        return String::new();
+    }
+
+    let mut section = String::from("<h2>Preventative Actions</h2>");
+    if actions.incidents.is_empty() {
+        section.push_str("<p>None for this week's incidents</p>");
+    } else {
+        section.push_str("<ul>");
+        for (record, incident_actions) in &actions.incidents {
+            if incident_actions.is_empty() {
+                section.push_str(&format!(
+                    "<li><strong>{}:</strong> no follow-up linked in JIRA</li>",
+                    escape(&incident_label(record))
+                ));
+            }
+            for action in incident_actions {
+                section.push_str(&action_item(record, action));
+            }
+        }
+        section.push_str("</ul>");
+    }
+
+    if !actions.carried_over.is_empty() {
+        section.push_str("<h3>Open Actions Carried Over from Previous Weeks</h3><ul>");
+        for (record, action) in &actions.carried_over {
+            section.push_str(&action_item(record, action));
+        }
+        section.push_str("</ul>");
+    }
+    section
+}
+
+fn incident_section(incident: &ReportIncident, jira_server_id: Option<&str>) -> String {
+    let mut section = format!("<h3>{}</h3>", escape(&incident.header()));
+    if let Some(key) = &incident.jira_key {
+        section.push_str(&format!("<p>{}</p>", jira_macro(key, jira_server_id)));
+    }
+    if let Some(recurrence) = incident.recurrence {
+        section.push_str(&format!("<p><strong>Recurring:</strong> {}</p>", escape(recurrence)));
+    }
+    for paragraph in incident.body().split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        section.push_str(&format!("<p>{}</p>", escape(paragraph).replace('\n', "<br />")));
+    }
+    section
+}
+
+/// Renders the report in Confluence storage format: the KPIs as page properties,
+/// then the actions and a section per incident with its JIRA issue embedded.
+pub fn render_storage(report: &Report, jira_server_id: Option<&str>) -> String {
+    let mut page = format!(
+        "<p><strong>Week {}</strong> ({} - {}), all times UTC</p>",
+        report.week_number,
+        report.week_start.format("%B %d"),
+        report.week_end.format("%B %d")
+    );
+    if let Some(summary) = report.ai.and_then(|ai| ai.summary.as_deref()) {
+        page.push_str(&format!("<p>{}</p>", escape(summary)));
+    }
+    page.push_str(&page_properties(report));
+    page.push_str(&trends(report));
+    page.push_str(&actions_section(report));
+
+    page.push_str("<h2>Incidents</h2>");
+    if report.incidents.is_empty() {
+        page.push_str("<p>No incidents this week.</p>");
+    }
+    for incident in &report.incidents {
+        page.push_str(&incident_section(incident, jira_server_id));
+    }
+    page
+}
+
+/// Atlassian Cloud takes the same email and API token for Confluence as for JIRA.
+fn auth_header(email: &str, token: &str) -> String {
+    format!("Basic {}", STANDARD.encode(format!("{}:{}", email, token)))
+}
+
+async fn check(response: reqwest::Response, action: &str) -> Result<Value, Box<dyn Error>> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Confluence rejected the request to {} with {}: {}", action, status, body.trim()).into());
+    }
+    Ok(response.json().await?)
+}
+
+/// Finds this week's page by title, returning its ID and current version.
+async fn find_page(
+    client: &reqwest::Client,
+    config: &ConfluenceConfig,
+    title: &str,
+    auth: &str,
+) -> Result<Option<(String, u64)>, Box<dyn Error>> {
+    let response = client
+        .get(format!("{}/rest/api/content", config.base_url))
+        .query(&[("spaceKey", config.space.as_str()), ("title", title), ("expand", "version,ancestors")])
+        .header("Authorization", auth)
+        .header("Accept", "application/json")
+        .send()
+        .await?;
+    let found = check(response, "find the page").await?;
+    existing_page(&found, config, title)
+}
+
+/// Picks the page under `CONFLUENCE_PARENT_ID` from a title search. Titles are unique within a space,
+/// so a page elsewhere in it can't be updated or created alongside and is reported instead.
+fn existing_page(
+    found: &Value,
+    config: &ConfluenceConfig,
+    title: &str,
+) -> Result<Option<(String, u64)>, Box<dyn Error>> {
+    let Some(page) = found["results"].as_array().and_then(|results| results.first()) else {
+        return Ok(None);
+    };
+
+    let under_parent = page["ancestors"]
+        .as_array()
+        .is_some_and(|ancestors| ancestors.iter().any(|ancestor| ancestor["id"].as_str() == Some(&config.parent_id)));
+    if !under_parent {
+        return Err(format!(
+            "The page \"{}\" already exists in space {} but not under page {}: {}",
+            title,
+            config.space,
+            config.parent_id,
+            page_url(config, page)
+        )
+        .into());
+    }
+
+    let id = page["id"].as_str().ok_or("Confluence returned a page without an ID")?.to_string();
+    let version = page["version"]["number"].as_u64().ok_or("Confluence returned a page without a version")?;
+    Ok(Some((id, version)))
+}
+
+fn page_url(config: &ConfluenceConfig, page: &Value) -> String {
+    match page["_links"]["webui"].as_str() {
+        Some(path) => format!("{}{}", config.base_url, path),
+        None => format!("{}/pages/viewpage.action?pageId={}", config.base_url, page["id"].as_str().unwrap_or("")),
+    }
+}
+
+/// Publishes the report as a child page of `CONFLUENCE_PARENT_ID`, creating this week's page
+/// or adding a new version of it. A dry run writes the page body to `OUTBOX_DIR` instead.
+pub async fn publish_to_confluence(
+    report: &Report<'_>,
+    email: &str,
+    token: &str,
+    dry_run: bool,
+) -> Result<(), Box<dyn Error>> {
+    let config = ConfluenceConfig::from_env()?;
+    let title = render_subject(&config.title, report.week_number, &report.week_start, &report.week_end);
+    let storage = render_storage(report, config.jira_server_id.as_deref());
+
+    if dry_run {
+        let dir = outbox_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}-confluence.xml", report.file_stem()));
+        fs::write(&path, &storage)?;
+        info!("Dry run: wrote the Confluence page \"{}\" to {}", title, path.display());
+        return Ok(());
+    }
+
+    if token.is_empty() {
+        return Err("JIRA_TOKEN must be set to publish the report to Confluence".into());
+    }
+    let auth = auth_header(email, token);
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+
+    let mut page = json!({
+        "type": "page",
+        "title": title,
+        "space": {"key": config.space},
+        "ancestors": [{"id": config.parent_id}],
+        "body": {"storage": {"value": storage, "representation": "storage"}}
+    });
+
+    match find_page(&client, &config, &title, &auth).await? {
+        Some((id, version)) => {
+            page["id"] = json!(id);
+            page["version"] = json!({"number": version + 1, "message": "Regenerated by weekly-status-report"});
+            let response = client
+                .put(format!("{}/rest/api/content/{}", config.base_url, id))
+                .header("Authorization", &auth)
+                .json(&page)
+                .send()
+                .await?;
+            let published = check(response, "update the page").await?;
+            info!("Updated Confluence page \"{}\" to version {}: {}", title, version + 1, page_url(&config, &published));
+        }
+        None => {
+            // Labels let a page properties report on the parent find every week's page
+            page["metadata"] = json!({"labels": [{"prefix": "global", "name": config.label}]});
+            let response = client
+                .post(format!("{}/rest/api/content", config.base_url))
+                .header("Authorization", &auth)
+                .json(&page)
+                .send()
+                .await?;
+            let published = check(response, "create the page").await?;
+            info!("Created Confluence page \"{}\": {}", title, page_url(&config, &published));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::{outage, ReportFixture};
+
+    fn config() -> ConfluenceConfig {
+        ConfluenceConfig {
+            base_url: "https://example.atlassian.net/wiki".to_string(),
+            space: "OPS".to_string(),
+            parent_id: "100".to_string(),
+            title: DEFAULT_TITLE.to_string(),
+            label: DEFAULT_LABEL.to_string(),
+            jira_server_id: None,
+        }
+    }
+
+    fn search(ancestors: &[&str]) -> Value {
+        let ancestors: Vec<Value> = ancestors.iter().map(|id| json!({ "id": id, "title": "Stability" })).collect();
+        json!({ "results": [{
+            "id": "200",
+            "title": "Weekly Stability Report – 2026 Week 41",
+            "version": { "number": 3 },
+            "ancestors": ancestors,
+            "_links": { "webui": "/spaces/OPS/pages/200" }
+        }] })
+    }
+
+    #[test]
+    fn renders_storage_format() {
+        let fixture = ReportFixture::new(vec![outage(1, "Pool <db> & cache \"warm\"")]);
+        let page = render_storage(&fixture.report(), Some("abc-123"));
+
+        assert!(page.starts_with("<p><strong>Week 41</strong> (October 11 - October 17), all times UTC</p>"));
+        assert!(page.contains(
+            "<ac:structured-macro ac:name=\"details\"><ac:rich-text-body><table><tbody>\
+             <tr><th>Week</th><td>41</td></tr><tr><th>Period</th><td>2026-10-11 - 2026-10-17</td></tr>"
+        ));
+        assert!(page.contains(
+            "<ac:structured-macro ac:name=\"jira\"><ac:parameter ac:name=\"key\">OPS-1</ac:parameter>\
+             <ac:parameter ac:name=\"serverId\">abc-123</ac:parameter></ac:structured-macro>"
+        ));
+        assert!(page.contains("Pool &lt;db&gt; &amp; cache &quot;warm&quot;"));
+        assert!(!page.contains("<db>"));
+
+        let without_server = render_storage(&fixture.report(), None);
+        assert!(without_server.contains(
+            "<ac:structured-macro ac:name=\"jira\"><ac:parameter ac:name=\"key\">OPS-1</ac:parameter>\
+             </ac:structured-macro>"
+        ));
+        let quiet = ReportFixture::new(Vec::new());
+        assert!(render_storage(&quiet.report(), None).ends_with("<h2>Incidents</h2><p>No incidents this week.</p>"));
+    }
+
+    #[test]
+    fn only_updates_the_page_under_the_parent() {
+        let title = "Weekly Stability Report – 2026 Week 41";
+        assert_eq!(existing_page(&search(&["1", "100"]), &config(), title).unwrap(), Some(("200".to_string(), 3)));
+        assert_eq!(existing_page(&json!({ "results": [] }), &config(), title).unwrap(), None);
+
+        let elsewhere = existing_page(&search(&["1", "900"]), &config(), title).unwrap_err().to_string();
+        assert!(elsewhere.contains("not under page 100"));
+        assert!(elsewhere.contains("https://example.atlassian.net/wiki/spaces/OPS/pages/200"));
+        assert!(existing_page(&search(&[]), &config(), title).is_err());
+    }
+}
@@ -0,0 +1,465 @@
+use chrono::NaiveDate;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::cursor::Show;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, IsTerminal, Stderr};
+use std::panic::{self, PanicHookInfo};
+use std::sync::Arc;
+
+use crate::actions::incident_actions;
+use crate::ai_report::{incident_narrative, regenerate_incident, NarrativeGenerator};
+use crate::editor::edit_text;
+use crate::llm::{LlmConfig, ProgressStyle};
+use crate::prompts::PromptOutage;
+use crate::snapshot::LlmSnapshot;
+use crate::{extract_jira_key, extract_rca_sections, format_description, parse_date, JiraIssue, OutageRecord};
+
+type DashboardTerminal = Terminal<CrosstermBackend<Stderr>>;
+type PanicHook = dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static;
+
+const HELP: &str = "↑/↓ select  space include/exclude  e edit  r regenerate  PgUp/PgDn scroll  x export  q quit";
+
+/// The narratives shown in the dashboard and how to regenerate them, when AI is on.
+pub struct DashboardAi<'a> {
+    pub llm: &'a mut LlmSnapshot,
+    pub generator: NarrativeGenerator<'a>,
+}
+
+/// The week's incidents as they'll go into the report.
+pub struct Dashboard<'a> {
+    pub week_number: u32,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    pub outages: &'a [OutageRecord],
+    pub prompt_outages: &'a [PromptOutage],
+    pub jira_details: &'a HashMap<String, JiraIssue>,
+    pub ai: Option<DashboardAi<'a>>,
+}
+
+/// How the dashboard was closed.
+pub enum DashboardOutcome {
+    /// Write the report with the incidents marked `true` in `included`
+    Export { included: Vec<bool>, edited: bool },
+    Quit,
+}
+
+struct State {
+    list: ListState,
+    included: Vec<bool>,
+    /// Whether a narrative was edited or regenerated
+    edited: bool,
+    scroll: u16,
+    status: String,
+    confirm_quit: bool,
+}
+
+/// Keeps the items whose entry in `included` is `true`.
+pub fn retain_included<T>(items: Vec<T>, included: &[bool]) -> Vec<T> {
+    items
+        .into_iter()
+        .zip(included)
+        .filter_map(|(item, &keep)| keep.then_some(item))
+        .collect()
+}
+
+fn severity_style(severity: &str) -> Style {
+    match severity.trim() {
+        "S1" => Style::default().fg(Color::Red),
+        "S2" => Style::default().fg(Color::LightRed),
+        "S3" => Style::default().fg(Color::Yellow),
+        "S4" => Style::default().fg(Color::Blue),
+        _ => Style::default().fg(Color::Gray),
+    }
+}
+
+fn heading(text: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        text.to_string(),
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    ))
+}
+
+fn field(label: &str, value: &str) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{}: ", label), Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(value.trim().to_string()),
+    ])
+}
+
+fn push_text(lines: &mut Vec<Line<'static>>, text: &str) {
+    lines.extend(text.trim().lines().map(|line| Line::from(line.to_string())));
+}
+
+/// Where the incident's narrative comes from, and the narrative itself.
+fn narrative(dashboard: &Dashboard, index: usize) -> (String, String) {
+    let record = &dashboard.outages[index];
+    let standard = format_description(&record.cause, &record.solution);
+    let Some(ai) = &dashboard.ai else {
+        return ("standard entry, AI is off".to_string(), standard);
+    };
+    match incident_narrative(ai.llm, index) {
+        Ok(text) if ai.llm.incidents[index].edited.is_some() => ("edited".to_string(), text),
+        Ok(text) => ("AI".to_string(), text),
+        Err(e) => (format!("standard entry, the AI narrative failed: {}", e), standard),
+    }
+}
+
+/// The CSV row, JIRA issue and RCA for one incident, merged.
+fn details(dashboard: &Dashboard, index: usize) -> Vec<Line<'static>> {
+    let record = &dashboard.outages[index];
+    let prompt_outage = &dashboard.prompt_outages[index];
+    let issue = extract_jira_key(&record.ticket)
+        .and_then(|key| dashboard.jira_details.get(&key).map(|issue| (key, issue)));
+    let mut lines = vec![
+        heading("CSV"),
+        field("Date", &record.date),
+        field("Service", &record.service),
+        field("Severity", &record.severity),
+    ];
+    if !record.duration.trim().is_empty() {
+        lines.push(field("Duration", &format!("{} min", record.duration.trim())));
+    }
+    lines.push(field("Times", &format!("{} - {} UTC", prompt_outage.start_time, prompt_outage.end_time)));
+    if let Some(detected) = record.detected.as_deref().filter(|detected| !detected.trim().is_empty()) {
+        lines.push(field("Detected", detected));
+    }
+    lines.push(field("Ticket", &record.ticket));
+    lines.push(field("Cause", &record.cause));
+    lines.push(field("Solution", &record.solution));
+    if let Some(recurrence) = &prompt_outage.recurrence {
+        lines.push(field("Recurring", recurrence));
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("JIRA"));
+    match &issue {
+        Some((key, issue)) => {
+            let fields = &issue.fields;
+            lines.push(field("Key", key));
+            lines.push(field("Summary", fields.summary.as_deref().unwrap_or("-")));
+            lines.push(field("Status", fields.status.as_ref().map(|status| status.name.as_str()).unwrap_or("-")));
+            lines.push(field(
+                "Assignee",
+                fields.assignee.as_ref().map(|user| user.display_name.as_str()).unwrap_or("Unassigned"),
+            ));
+            if !fields.components.is_empty() {
+                let components: Vec<&str> = fields.components.iter().map(|component| component.name.as_str()).collect();
+                lines.push(field("Components", &components.join(", ")));
+            }
+        }
+        None => lines.push(Line::from("No JIRA details, using the CSV only")),
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("RCA"));
+    let rca = issue
+        .and_then(|(_, issue)| issue.fields.description.as_deref())
+        .map(extract_rca_sections)
+        .unwrap_or_default();
+    if let Some(root_cause) = rca.rca.as_deref().or(rca.related_line.as_deref()) {
+        push_text(&mut lines, root_cause);
+    }
+    if let Some(measures) = &rca.preventative_measures {
+        lines.push(Line::from(Span::styled("Preventative measures", Style::default().add_modifier(Modifier::BOLD))));
+        push_text(&mut lines, measures);
+    }
+    if rca.rca.is_none() && rca.related_line.is_none() && rca.preventative_measures.is_none() {
+        lines.push(Line::from("No RCA in the JIRA description"));
+    }
+
+    let actions = incident_actions(record, dashboard.jira_details);
+    if !actions.is_empty() {
+        lines.push(Line::default());
+        lines.push(heading("Preventative Actions"));
+        for action in actions {
+            lines.push(Line::from(format!(
+                "- {} {}{}",
+                action.key,
+                action.summary.as_deref().unwrap_or("").trim(),
+                action.progress()
+            )));
+        }
+    }
+
+    let (source, text) = narrative(dashboard, index);
+    lines.push(Line::default());
+    lines.push(heading(&format!("Narrative ({})", source)));
+    push_text(&mut lines, &text);
+    lines
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard, state: &mut State) {
+    let [title_area, body_area, status_area] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(2)])
+        .areas(frame.area());
+    let [list_area, details_area] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(38), Constraint::Percentage(62)])
+        .areas(body_area);
+
+    let included = state.included.iter().filter(|included| **included).count();
+    let title = format!(
+        "Week {} ({} - {}), {} of {} incidents included{}",
+        dashboard.week_number,
+        dashboard.week_start.format("%B %d"),
+        dashboard.week_end.format("%B %d"),
+        included,
+        state.included.len(),
+        if dashboard.ai.is_some() { "" } else { ", AI is off" }
+    );
+    frame.render_widget(Paragraph::new(title).style(Style::default().add_modifier(Modifier::BOLD)), title_area);
+
+    let items: Vec<ListItem> = dashboard
+        .outages
+        .iter()
+        .zip(&state.included)
+        .enumerate()
+        .map(|(index, (record, &included))| {
+            let date = parse_date(&record.date)
+                .map(|date| date.format("%b %d").to_string())
+                .unwrap_or_else(|| record.date.clone());
+            let edited = dashboard
+                .ai
+                .as_ref()
+                .and_then(|ai| ai.llm.incidents.get(index))
+                .is_some_and(|exchange| exchange.edited.is_some());
+            let mut spans = vec![
+                Span::raw(if included { "[x] " } else { "[ ] " }),
+                Span::raw(format!("{} ", date)),
+                Span::styled(format!("{:<3}", record.severity.trim()), severity_style(&record.severity)),
+                Span::raw(record.service.trim().to_string()),
+            ];
+            if edited {
+                spans.push(Span::styled(" (edited)", Style::default().fg(Color::Gray)));
+            }
+            let item = ListItem::new(Line::from(spans));
+            if included {
+                item
+            } else {
+                item.style(Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT))
+            }
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Incidents "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, list_area, &mut state.list);
+
+    let details_block = Block::default().borders(Borders::ALL).title(" Details ");
+    let details = match state.list.selected() {
+        Some(index) => Paragraph::new(details(dashboard, index)),
+        None => Paragraph::new("No incidents this week."),
+    };
+    frame.render_widget(
+        details.block(details_block).wrap(Wrap { trim: false }).scroll((state.scroll, 0)),
+        details_area,
+    );
+
+    let status = Paragraph::new(vec![
+        Line::from(state.status.clone()),
+        Line::from(Span::styled(HELP, Style::default().fg(Color::Gray))),
+    ]);
+    frame.render_widget(status, status_area);
+}
+
+/// Leaves the dashboard's screen so an editor can use the terminal.
+fn suspend(terminal: &mut DashboardTerminal) -> Result<(), Box<dyn Error>> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    Ok(())
+}
+
+fn resume(terminal: &mut DashboardTerminal) -> Result<(), Box<dyn Error>> {
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(())
+}
+
+fn edit_narrative(
+    terminal: &mut DashboardTerminal,
+    dashboard: &mut Dashboard,
+    state: &mut State,
+    index: usize,
+) -> Result<String, Box<dyn Error>> {
+    let (_, current) = narrative(dashboard, index);
+    let Some(ai) = dashboard.ai.as_mut() else {
+        return Ok("AI is off, so the standard entry is used and there's no narrative to edit".to_string());
+    };
+
+    suspend(terminal)?;
+    let edited = edit_text(&current, "narrative.txt");
+    resume(terminal)?;
+
+    let edited = edited?;
+    let edited = edited.trim();
+    if edited.is_empty() {
+        Ok("The narrative was empty, keeping the previous one".to_string())
+    } else if edited == current.trim() {
+        Ok("The narrative wasn't changed".to_string())
+    } else {
+        ai.llm.incidents[index].edited = Some(edited.to_string());
+        state.edited = true;
+        Ok("Narrative updated".to_string())
+    }
+}
+
+async fn regenerate(
+    terminal: &mut DashboardTerminal,
+    dashboard: &mut Dashboard<'_>,
+    state: &mut State,
+    index: usize,
+) -> Result<String, Box<dyn Error>> {
+    let Some(ai) = dashboard.ai.as_ref() else {
+        return Ok("AI is off, set USE_AI=true to generate narratives".to_string());
+    };
+    state.status = format!("Requesting a new narrative for {}...", dashboard.outages[index].service.trim());
+    terminal.draw(|frame| draw(frame, dashboard, state))?;
+
+    // Streamed tokens would be written over the dashboard
+    let config = LlmConfig {
+        progress: ProgressStyle::None,
+        ..ai.generator.config.clone()
+    };
+    let generator = NarrativeGenerator { config: &config, ..ai.generator };
+    let result = regenerate_incident(
+        &generator,
+        &dashboard.prompt_outages[index],
+        dashboard.week_number,
+        &dashboard.week_start,
+        &dashboard.week_end,
+    )
+    .await;
+    // Anything logged while waiting went to the same terminal
+    terminal.clear()?;
+
+    match result {
+        Ok(exchange) => {
+            if let Some(ai) = dashboard.ai.as_mut() {
+                ai.llm.incidents[index] = exchange;
+            }
+            state.edited = true;
+            Ok("New narrative generated".to_string())
+        }
+        Err(e) => Ok(format!("Could not regenerate the narrative, keeping the previous one: {}", e)),
+    }
+}
+
+async fn event_loop(
+    terminal: &mut DashboardTerminal,
+    dashboard: &mut Dashboard<'_>,
+) -> Result<DashboardOutcome, Box<dyn Error>> {
+    let count = dashboard.outages.len();
+    let mut state = State {
+        list: ListState::default().with_selected((count > 0).then_some(0)),
+        included: vec![true; count],
+        edited: false,
+        scroll: 0,
+        status: String::new(),
+        confirm_quit: false,
+    };
+
+    loop {
+        terminal.draw(|frame| draw(frame, dashboard, &mut state))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let confirm_quit = std::mem::take(&mut state.confirm_quit);
+        let selected = state.list.selected();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                let changed = state.edited || state.included.contains(&false);
+                if !changed || confirm_quit {
+                    return Ok(DashboardOutcome::Quit);
+                }
+                state.confirm_quit = true;
+                state.status = "Quit without writing the report? Press q again to quit, x to export".to_string();
+            }
+            KeyCode::Char('x') => {
+                return Ok(DashboardOutcome::Export {
+                    included: state.included,
+                    edited: state.edited,
+                })
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.list.select_previous();
+                state.scroll = 0;
+            }
+            // select_next goes past the end, the list only clamps it when drawn
+            KeyCode::Down | KeyCode::Char('j') if selected.is_some_and(|index| index + 1 < count) => {
+                state.list.select_next();
+                state.scroll = 0;
+            }
+            KeyCode::PageDown => state.scroll = state.scroll.saturating_add(10),
+            KeyCode::PageUp => state.scroll = state.scroll.saturating_sub(10),
+            KeyCode::Char(' ') => {
+                if let Some(index) = selected {
+                    state.included[index] = !state.included[index];
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(index) = selected {
+                    let status = edit_narrative(terminal, dashboard, &mut state, index);
+                    state.status = status.unwrap_or_else(|e| e.to_string());
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(index) = selected {
+                    state.status = regenerate(terminal, dashboard, &mut state, index).await?;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Shows the week's incidents with their CSV, JIRA and RCA details, to include or exclude them,
+/// edit narratives in `$EDITOR` and regenerate single AI narratives before the report is written.
+/// The dashboard is drawn on stderr so stdout can still be redirected to a file.
+pub async fn run_dashboard(dashboard: &mut Dashboard<'_>) -> Result<DashboardOutcome, Box<dyn Error>> {
+    if !io::stderr().is_terminal() {
+        return Err("The dashboard needs an interactive terminal on stderr".into());
+    }
+
+    // Restore the terminal before a panic message is printed, or it stays in raw mode on the dashboard's screen
+    let previous_hook: Arc<PanicHook> = Arc::from(panic::take_hook());
+    let hook = Arc::clone(&previous_hook);
+    panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stderr(), LeaveAlternateScreen, Show);
+        hook(info);
+    }));
+
+    let outcome = run_in_terminal(dashboard).await;
+
+    let _ = panic::take_hook();
+    panic::set_hook(Box::new(move |info| previous_hook(info)));
+    outcome
+}
+
+async fn run_in_terminal(dashboard: &mut Dashboard<'_>) -> Result<DashboardOutcome, Box<dyn Error>> {
+    enable_raw_mode()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stderr()))?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+
+    let outcome = event_loop(&mut terminal, dashboard).await;
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    outcome
+}
@@ -0,0 +1,33 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::process::{self, Command};
+
+/// The editor to open, from `VISUAL` or `EDITOR`, defaulting to `vi`.
+fn editor_command() -> Vec<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // Allows arguments, e.g. EDITOR="code --wait"
+    editor.split_whitespace().map(str::to_string).collect()
+}
+
+/// Opens `text` in the user's editor and returns what was saved. `name` ends the temporary
+/// file's name, so editors can pick syntax highlighting from its extension.
+pub fn edit_text(text: &str, name: &str) -> Result<String, Box<dyn Error>> {
+    let path = env::temp_dir().join(format!("weekly-status-report-{}-{}", process::id(), name));
+    fs::write(&path, text)?;
+
+    let command = editor_command();
+    let status = Command::new(&command[0]).args(&command[1..]).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status.map_err(|e| format!("Could not start the editor {}: {}", command[0], e))?;
+    if !status.success() {
+        return Err(format!("The editor {} exited with {}", command[0], status).into());
+    }
+    Ok(edited?)
+}
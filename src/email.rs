@@ -0,0 +1,363 @@
+use chrono::NaiveDate;
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use log::{info, warn};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::html::render_html;
+use crate::report::{outbox_dir, Report};
+
+const DEFAULT_SUBJECT: &str = "Weekly Stability Report – Week {n}";
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS, usually port 587
+    StartTls,
+    /// TLS from the start, usually port 465
+    Tls,
+    /// No encryption, for local SMTP catchers only
+    None,
+}
+
+impl SmtpSecurity {
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" | "ssl" => Ok(SmtpSecurity::Tls),
+            "none" => Ok(SmtpSecurity::None),
+            other => Err(format!("Unknown SMTP_SECURITY '{}', expected starttls, tls or none", other).into()),
+        }
+    }
+
+    fn default_port(&self) -> u16 {
+        match self {
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::None => 25,
+        }
+    }
+}
+
+impl fmt::Display for SmtpSecurity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmtpSecurity::StartTls => write!(f, "starttls"),
+            SmtpSecurity::Tls => write!(f, "tls"),
+            SmtpSecurity::None => write!(f, "none"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    /// Only needed to send, not for a dry run
+    pub host: Option<String>,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: Mailbox,
+    pub to: Vec<Mailbox>,
+    pub cc: Vec<Mailbox>,
+    pub bcc: Vec<Mailbox>,
+    pub subject: String,
+}
+
+fn optional_var(name: &str) -> Option<String> {
+    env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Reads a comma-separated list of addresses from `name`, which may be unset.
+fn mailboxes(name: &str) -> Result<Vec<Mailbox>, Box<dyn Error>> {
+    parse_mailboxes(name, &optional_var(name).unwrap_or_default())
+}
+
+/// Parses a comma-separated list such as `Jane Doe <jane@example.com>, ops@example.com`.
+/// `name` is the variable it came from, for the error message.
+fn parse_mailboxes(name: &str, list: &str) -> Result<Vec<Mailbox>, Box<dyn Error>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| format!("Invalid address '{}' in {}: {}", address, name, e).into())
+        })
+        .collect()
+}
+
+impl EmailConfig {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let security = SmtpSecurity::parse(&env::var("SMTP_SECURITY").unwrap_or_else(|_| "starttls".to_string()))?;
+        let port = match optional_var("SMTP_PORT") {
+            Some(port) => port.parse().map_err(|e| format!("Invalid SMTP_PORT '{}': {}", port, e))?,
+            None => security.default_port(),
+        };
+        let from = optional_var("EMAIL_FROM")
+            .ok_or("EMAIL_FROM must be set to send the report")?
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid EMAIL_FROM: {}", e))?;
+        let to = mailboxes("EMAIL_TO")?;
+        if to.is_empty() {
+            return Err("EMAIL_TO must list at least one recipient".into());
+        }
+
+        Ok(EmailConfig {
+            host: optional_var("SMTP_HOST"),
+            port,
+            security,
+            username: optional_var("SMTP_USERNAME"),
+            password: optional_var("SMTP_PASSWORD"),
+            from,
+            to,
+            cc: mailboxes("EMAIL_CC")?,
+            bcc: mailboxes("EMAIL_BCC")?,
+            subject: env::var("EMAIL_SUBJECT").unwrap_or_else(|_| DEFAULT_SUBJECT.to_string()),
+        })
+    }
+}
+
+/// Fills in the subject template: `{n}` is the week number, `{year}` its ISO year, `{start}` and `{end}` the dates.
+pub fn render_subject(template: &str, week_number: u32, week_start: &NaiveDate, week_end: &NaiveDate) -> String {
+    template
+        .replace("{n}", &week_number.to_string())
+        .replace("{year}", &week_start.format("%G").to_string())
+        .replace("{start}", &week_start.format("%B %d").to_string())
+        .replace("{end}", &week_end.format("%B %d").to_string())
+}
+
+/// Builds the email with the text report as the plain-text alternative to the HTML one.
+/// Bcc recipients are only kept in the headers when `keep_bcc` is set, for dry runs.
+pub fn build_message(
+    config: &EmailConfig,
+    subject: &str,
+    text: &str,
+    html: Option<&str>,
+    keep_bcc: bool,
+) -> Result<Message, Box<dyn Error>> {
+    let mut builder = Message::builder().from(config.from.clone()).subject(subject);
+    for mailbox in &config.to {
+        builder = builder.to(mailbox.clone());
+    }
+    for mailbox in &config.cc {
+        builder = builder.cc(mailbox.clone());
+    }
+    for mailbox in &config.bcc {
+        builder = builder.bcc(mailbox.clone());
+    }
+    if keep_bcc {
+        builder = builder.keep_bcc();
+    }
+
+    let message = match html {
+        Some(html) => builder.multipart(MultiPart::alternative_plain_html(text.to_string(), html.to_string()))?,
+        None => builder.singlepart(SinglePart::builder().header(ContentType::TEXT_PLAIN).body(text.to_string()))?,
+    };
+    Ok(message)
+}
+
+/// Writes the full MIME message to `dir/name.eml`, which mail clients can open as a draft.
+pub fn write_message(dir: &Path, name: &str, message: &Message) -> Result<PathBuf, Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.eml", name));
+    fs::write(&path, message.formatted())?;
+    Ok(path)
+}
+
+pub async fn send_message(config: &EmailConfig, message: Message) -> Result<(), Box<dyn Error>> {
+    let host = config.host.as_deref().ok_or("SMTP_HOST must be set to send the report")?;
+    let mut transport = match config.security {
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    }
+    .port(config.port);
+    if let Some(username) = &config.username {
+        transport = transport.credentials(Credentials::new(
+            username.clone(),
+            config.password.clone().unwrap_or_default(),
+        ));
+    }
+
+    info!(
+        "Sending report via {}:{} ({}) to {} recipient(s)",
+        host,
+        config.port,
+        config.security,
+        config.to.len() + config.cc.len() + config.bcc.len()
+    );
+    transport
+        .build()
+        .send(message)
+        .await
+        .map_err(|e| format!("Failed to send the report email via {}:{}: {}", host, config.port, e))?;
+    Ok(())
+}
+
+/// Emails the report as HTML with the text report as its alternative, or writes the message
+/// to `OUTBOX_DIR` instead on a dry run.
+pub async fn email_report(report: &Report<'_>, text: &str, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let config = EmailConfig::from_env()?;
+    let subject = render_subject(&config.subject, report.week_number, &report.week_start, &report.week_end);
+    let html = match render_html(report) {
+        Ok(html) => Some(html),
+        Err(e) => {
+            warn!("Could not render the HTML email, sending plain text only: {}", e);
+            None
+        }
+    };
+
+    if dry_run {
+        let message = build_message(&config, &subject, text, html.as_deref(), true)?;
+        let path = write_message(&outbox_dir(), &report.file_stem(), &message)?;
+        info!("Dry run: wrote the email to {} instead of sending it", path.display());
+        return Ok(());
+    }
+
+    let message = build_message(&config, &subject, text, html.as_deref(), false)?;
+    send_message(&config, message).await?;
+    info!("Sent report email \"{}\"", subject);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn config() -> EmailConfig {
+        EmailConfig {
+            host: Some("127.0.0.1".to_string()),
+            port: 25,
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from: "Reports <reports@example.com>".parse().unwrap(),
+            to: vec!["exec@example.com".parse().unwrap()],
+            cc: vec!["ops@example.com".parse().unwrap()],
+            bcc: vec!["audit@example.com".parse().unwrap()],
+            subject: DEFAULT_SUBJECT.to_string(),
+        }
+    }
+
+    fn formatted(message: &Message) -> String {
+        String::from_utf8(message.formatted()).unwrap()
+    }
+
+    #[test]
+    fn bcc_is_only_kept_for_dry_runs() {
+        let sent = formatted(&build_message(&config(), "Subject", "text", None, false).unwrap());
+        assert!(!sent.contains("Bcc:"));
+        assert!(!sent.contains("audit@example.com"));
+        assert!(sent.contains("Cc: ops@example.com"));
+
+        let dry_run = formatted(&build_message(&config(), "Subject", "text", None, true).unwrap());
+        assert!(dry_run.contains("Bcc: audit@example.com"));
+    }
+
+    #[test]
+    fn html_email_is_multipart_alternative_with_text_first() {
+        let message = formatted(&build_message(&config(), "Subject", "TEXT BODY", Some("<p>HTML BODY</p>"), false).unwrap());
+        assert!(message.contains("Content-Type: multipart/alternative"));
+
+        let plain = message.find("Content-Type: text/plain").unwrap();
+        let html = message.find("Content-Type: text/html").unwrap();
+        assert!(plain < html, "the plain-text part must come first so clients prefer the HTML");
+        assert!(message[plain..html].contains("TEXT BODY"));
+        assert!(message[html..].contains("<p>HTML BODY</p>"));
+    }
+
+    #[test]
+    fn text_only_email_is_a_single_part() {
+        let message = formatted(&build_message(&config(), "Subject", "TEXT BODY", None, false).unwrap());
+        assert!(!message.contains("multipart"));
+        assert!(message.contains("Content-Type: text/plain"));
+        assert!(message.contains("TEXT BODY"));
+    }
+
+    #[test]
+    fn subject_placeholders_are_expanded() {
+        let start = NaiveDate::from_ymd_opt(2026, 10, 11).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert_eq!(
+            render_subject("Week {n} of {year}: {start} - {end}", 41, &start, &end),
+            "Week 41 of 2026: October 11 - October 17"
+        );
+        assert_eq!(render_subject(DEFAULT_SUBJECT, 41, &start, &end), "Weekly Stability Report – Week 41");
+    }
+
+    #[test]
+    fn address_lists_are_parsed_and_invalid_ones_rejected() {
+        let parsed = parse_mailboxes("EMAIL_TO", "Jane Doe <jane@example.com>, , ops@example.com").unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].name.as_deref(), Some("Jane Doe"));
+        assert_eq!(parsed[1].email.to_string(), "ops@example.com");
+        assert!(parse_mailboxes("EMAIL_CC", "").unwrap().is_empty());
+
+        let error = parse_mailboxes("EMAIL_CC", "ops@example.com, not an address").unwrap_err();
+        assert!(error.to_string().contains("'not an address' in EMAIL_CC"));
+    }
+
+    /// Accepts one message like a local SMTP catcher, returning the recipients and the data.
+    async fn catch_one_message(listener: TcpListener) -> (Vec<String>, String) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+        let mut recipients = Vec::new();
+        let mut data = String::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let command = line.to_uppercase();
+            let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                b"250 localhost\r\n"
+            } else if command.starts_with("RCPT TO:") {
+                recipients.push(line[8..].trim().to_string());
+                b"250 OK\r\n"
+            } else if command.starts_with("DATA") {
+                writer.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n").await.unwrap();
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    if line == "." {
+                        break;
+                    }
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                b"250 OK\r\n"
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 OK\r\n"
+            };
+            writer.write_all(reply).await.unwrap();
+        }
+        (recipients, data)
+    }
+
+    #[tokio::test]
+    async fn sends_to_a_local_smtp_catcher_with_bcc_only_in_the_envelope() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = EmailConfig {
+            port: listener.local_addr().unwrap().port(),
+            ..config()
+        };
+        let catcher = tokio::spawn(catch_one_message(listener));
+
+        let message = build_message(&config, "Weekly", "TEXT BODY", Some("<p>HTML BODY</p>"), false).unwrap();
+        send_message(&config, message).await.unwrap();
+
+        let (recipients, data) = catcher.await.unwrap();
+        assert_eq!(recipients, ["<exec@example.com>", "<ops@example.com>", "<audit@example.com>"]);
+        assert!(data.contains("Subject: Weekly"));
+        assert!(data.contains("TEXT BODY"));
+        assert!(!data.contains("audit@example.com"));
+    }
+}
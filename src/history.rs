@@ -0,0 +1,198 @@
+use chrono::{NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use std::error::Error;
+
+use crate::{extract_jira_key, parse_date, parse_duration_to_minutes, OutageRecord};
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS incidents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    date TEXT NOT NULL,
+    ticket TEXT NOT NULL,
+    jira_key TEXT,
+    service TEXT NOT NULL,
+    duration_raw TEXT NOT NULL,
+    duration_minutes INTEGER,
+    cause TEXT NOT NULL,
+    solution TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    detected TEXT,
+    updated_at TEXT NOT NULL,
+    UNIQUE (date, ticket, service)
+);
+
+CREATE TABLE IF NOT EXISTS jira_issues (
+    key TEXT PRIMARY KEY,
+    payload TEXT NOT NULL,
+    fetched_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    week_number INTEGER NOT NULL,
+    week_start TEXT NOT NULL,
+    week_end TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    ai_generated INTEGER NOT NULL,
+    model TEXT,
+    inputs TEXT NOT NULL,
+    output TEXT NOT NULL
+);
+"#;
+
+/// Everything that went into a generated report, stored for auditing and re-rendering.
+pub struct ReportRecord<'a> {
+    pub week_number: u32,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    pub ai_generated: bool,
+    pub model: Option<String>,
+    pub outages: &'a [OutageRecord],
+    pub jira_keys: Vec<String>,
+    pub output: &'a str,
+}
+
+/// A stored report as listed by `history list`.
+pub struct StoredReport {
+    pub id: i64,
+    pub week_number: u32,
+    pub week_start: NaiveDate,
+    pub generated_at: String,
+    pub ai_generated: bool,
+    pub model: Option<String>,
+}
+
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+impl HistoryStore {
+    pub fn open(path: &str) -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(HistoryStore { conn })
+    }
+
+    /// Inserts or refreshes normalized incidents, keyed by date, ticket and service.
+    pub fn upsert_incidents(&self, outages: &[OutageRecord]) -> Result<usize, Box<dyn Error>> {
+        let now = Utc::now().to_rfc3339();
+        let mut stmt = self.conn.prepare(
+            "INSERT INTO incidents (date, ticket, jira_key, service, duration_raw, duration_minutes, cause, solution, severity, detected, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT (date, ticket, service) DO UPDATE SET
+                jira_key = excluded.jira_key,
+                duration_raw = excluded.duration_raw,
+                duration_minutes = excluded.duration_minutes,
+                cause = excluded.cause,
+                solution = excluded.solution,
+                severity = excluded.severity,
+                detected = excluded.detected,
+                updated_at = excluded.updated_at",
+        )?;
+
+        let mut stored = 0;
+        for record in outages {
+            // Incidents are stored with ISO dates so they sort and range-query correctly
+            let Some(date) = parse_date(&record.date) else {
+                continue;
+            };
+            let duration_minutes = if record.duration.trim().is_empty() {
+                None
+            } else {
+                Some(parse_duration_to_minutes(&record.duration))
+            };
+
+            stmt.execute(params![
+                date.to_string(),
+                record.ticket,
+                extract_jira_key(&record.ticket),
+                record.service,
+                record.duration,
+                duration_minutes,
+                record.cause,
+                record.solution,
+                record.severity,
+                record.detected,
+                now,
+            ])?;
+            stored += 1;
+        }
+
+        Ok(stored)
+    }
+
+    pub fn save_jira_payload(&self, key: &str, payload: &str) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "INSERT INTO jira_issues (key, payload, fetched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (key) DO UPDATE SET payload = excluded.payload, fetched_at = excluded.fetched_at",
+            params![key, payload, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Stores a generated report along with its inputs and returns its id.
+    pub fn save_report(&self, report: &ReportRecord) -> Result<i64, Box<dyn Error>> {
+        let inputs = serde_json::json!({
+            "outages": report.outages,
+            "jira_keys": report.jira_keys,
+        });
+
+        self.conn.execute(
+            "INSERT INTO reports (week_number, week_start, week_end, generated_at, ai_generated, model, inputs, output)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                report.week_number,
+                report.week_start.to_string(),
+                report.week_end.to_string(),
+                Utc::now().to_rfc3339(),
+                report.ai_generated,
+                report.model,
+                inputs.to_string(),
+                report.output,
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Every stored report, newest first.
+    pub fn reports(&self) -> Result<Vec<StoredReport>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, week_number, week_start, generated_at, ai_generated, model FROM reports ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, bool>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })?;
+
+        let mut reports = Vec::new();
+        for row in rows {
+            let (id, week_number, week_start, generated_at, ai_generated, model) = row?;
+            reports.push(StoredReport {
+                id,
+                week_number,
+                week_start: week_start.parse()?,
+                generated_at,
+                ai_generated,
+                model,
+            });
+        }
+        Ok(reports)
+    }
+
+    /// The text of a stored report exactly as it was generated, `None` if there is no such report.
+    pub fn report_output(&self, id: i64) -> Result<Option<String>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare("SELECT output FROM reports WHERE id = ?1")?;
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use log::debug;
+use minijinja::Environment;
+use serde::Serialize;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::actions::{incident_label, PreventativeAction};
+use crate::charts::{email_charts, TableChart};
+use crate::metrics::MetricRow;
+use crate::parse_date;
+use crate::report::{issue_url, Report, ReportIncident};
+use crate::OutageRecord;
+
+// Built-in copy of templates/email.html.j2, used when EMAIL_TEMPLATE_DIR doesn't have one
+const BUILTIN_EMAIL_HTML: &str = include_str!("../templates/email.html.j2");
+
+/// How many KPIs get a tile in the header; the rest are listed below it.
+const KPI_TILES: usize = 3;
+
+#[derive(Debug, Serialize)]
+struct MetricView<'a> {
+    label: &'a str,
+    value: &'a str,
+    trend: Option<&'a str>,
+    /// Just the "vs last week" part of the trend, which fits in a tile
+    last_week: Option<&'a str>,
+    /// `up`, `down` or `flat` compared to last week
+    direction: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct IncidentView<'a> {
+    header: String,
+    date: String,
+    service: &'a str,
+    severity: &'a str,
+    duration: &'a str,
+    start_time: Option<&'a str>,
+    end_time: Option<&'a str>,
+    ticket_key: Option<&'a str>,
+    ticket_url: Option<&'a str>,
+    recurrence: Option<&'a str>,
+    narrative: Option<&'a str>,
+    /// The narrative, or the CSV cause and solution, split into paragraphs
+    paragraphs: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ActionView<'a> {
+    incident: String,
+    key: &'a str,
+    url: Option<String>,
+    summary: Option<&'a str>,
+    relationship: &'a str,
+    status: Option<&'a str>,
+    assignee: Option<&'a str>,
+    done: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ActionGroupView<'a> {
+    incident: String,
+    actions: Vec<ActionView<'a>>,
+}
+
+/// Everything `email.html.j2` can use.
+#[derive(Debug, Serialize)]
+struct EmailContext<'a> {
+    title: &'static str,
+    week_number: u32,
+    week_start: String,
+    week_end: String,
+    ai_generated: bool,
+    summary: Option<&'a str>,
+    kpis: Vec<MetricView<'a>>,
+    other_metrics: Vec<MetricView<'a>>,
+    /// Drawn as table cells, since Gmail and Outlook for Windows don't display SVG
+    charts: Vec<TableChart>,
+    incidents: Vec<IncidentView<'a>>,
+    action_groups: Vec<ActionGroupView<'a>>,
+    carried_over: Vec<ActionView<'a>>,
+}
+
+fn metric_view(row: &MetricRow) -> MetricView<'_> {
+    let last_week = row
+        .trend
+        .as_deref()
+        .and_then(|trend| trend.split(" | ").next())
+        .filter(|part| part.ends_with("vs last week"));
+    let direction = last_week.map(|part| {
+        if part.starts_with('▲') {
+            "up"
+        } else if part.starts_with('▼') {
+            "down"
+        } else {
+            "flat"
+        }
+    });
+
+    MetricView {
+        label: row.label,
+        value: &row.value,
+        trend: row.trend.as_deref(),
+        last_week,
+        direction,
+    }
+}
+
+fn incident_view<'a>(incident: &'a ReportIncident) -> IncidentView<'a> {
+    let record = incident.record;
+    let date = parse_date(&record.date)
+        .map(|d| d.format("%B %d").to_string())
+        .unwrap_or_else(|| record.date.clone());
+    let paragraphs = incident
+        .body()
+        .split("\n\n")
+        .map(|paragraph| paragraph.trim().to_string())
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect();
+
+    IncidentView {
+        header: incident.header(),
+        date,
+        service: record.service.trim(),
+        severity: record.severity.trim(),
+        duration: record.duration.trim(),
+        start_time: incident.start_time.as_deref(),
+        end_time: incident.end_time.as_deref(),
+        ticket_key: incident.jira_key.as_deref(),
+        ticket_url: incident.ticket_url(),
+        recurrence: incident.recurrence,
+        narrative: incident.narrative,
+        paragraphs,
+    }
+}
+
+fn action_view<'a>(record: &OutageRecord, action: &'a PreventativeAction) -> ActionView<'a> {
+    ActionView {
+        incident: incident_label(record),
+        key: &action.key,
+        url: issue_url(&record.ticket, &action.key),
+        summary: action.summary.as_deref().map(str::trim),
+        relationship: &action.relationship,
+        status: action.status.as_deref(),
+        assignee: action.assignee.as_deref(),
+        done: action.done,
+    }
+}
+
+fn email_context<'a>(report: &'a Report) -> EmailContext<'a> {
+    let metrics: Vec<MetricView> = report.metrics.iter().map(metric_view).collect();
+    let (kpis, other_metrics) = {
+        let mut kpis = metrics;
+        let other_metrics = kpis.split_off(KPI_TILES.min(kpis.len()));
+        (kpis, other_metrics)
+    };
+
+    EmailContext {
+        title: report.title(),
+        week_number: report.week_number,
+        week_start: report.week_start.format("%B %d").to_string(),
+        week_end: report.week_end.format("%B %d").to_string(),
+        ai_generated: report.ai.is_some(),
+        summary: report.ai.and_then(|ai| ai.summary.as_deref()),
+        kpis,
+        other_metrics,
+        charts: email_charts(report),
+        incidents: report.incidents.iter().map(incident_view).collect(),
+        action_groups: report
+            .actions
+            .incidents
+            .iter()
+            .map(|(record, actions)| ActionGroupView {
+                incident: incident_label(record),
+                actions: actions.iter().map(|action| action_view(record, action)).collect(),
+            })
+            .collect(),
+        carried_over: report
+            .actions
+            .carried_over
+            .iter()
+            .map(|(record, action)| action_view(record, action))
+            .collect(),
+    }
+}
+
+/// Loads `email.html.j2` from `EMAIL_TEMPLATE_DIR` (default `templates`), falling back to the built-in one.
+fn load_template() -> Result<String, Box<dyn Error>> {
+    let dir = env::var("EMAIL_TEMPLATE_DIR").unwrap_or_else(|_| "templates".to_string());
+    let path = Path::new(&dir).join("email.html.j2");
+    if !path.exists() {
+        debug!("No email template at {}, using the built-in one", path.display());
+        return Ok(BUILTIN_EMAIL_HTML.to_string());
+    }
+
+    debug!("Using email template {}", path.display());
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read email template {}: {}", path.display(), e).into())
+}
+
+/// Renders the report as an HTML email with inline styles. The text report is its plain-text alternative.
+pub fn render_html(report: &Report) -> Result<String, Box<dyn Error>> {
+    let template = load_template()?;
+    let mut env = Environment::new();
+    // The .html name turns on HTML escaping for everything the template prints
+    env.add_template("email.html", &template)?;
+    Ok(env.get_template("email.html")?.render(email_context(report))?)
+}
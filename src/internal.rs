@@ -0,0 +1,68 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::structured::Recommendation;
+use crate::verify::Finding;
+
+/// AI output meant for the team rather than the executive email.
+#[derive(Debug, Serialize)]
+pub struct InternalNotes<'a> {
+    pub week_number: u32,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    pub recommendations: &'a [Recommendation],
+    pub findings: &'a [Finding],
+}
+
+impl InternalNotes<'_> {
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        text.push_str("INTERNAL - NOT PART OF THE EXECUTIVE REPORT\n");
+        text.push_str(&format!(
+            "Week {} ({} - {})\n\n",
+            self.week_number,
+            self.week_start.format("%B %d"),
+            self.week_end.format("%B %d")
+        ));
+
+        text.push_str("AI RECOMMENDATIONS\n");
+        if self.recommendations.is_empty() {
+            text.push_str("None\n");
+        }
+        for recommendation in self.recommendations {
+            let target = match (recommendation.incident, &recommendation.service) {
+                (Some(incident), Some(service)) => format!("Incident {}, {}", incident, service),
+                (Some(incident), None) => format!("Incident {}", incident),
+                (None, Some(service)) => service.clone(),
+                (None, None) => "General".to_string(),
+            };
+            text.push_str(&format!("- [{}] {}\n", target, recommendation.text));
+        }
+
+        text.push_str("\nAI REPORT CHECKS\n");
+        if self.findings.is_empty() {
+            text.push_str("None\n");
+        }
+        for finding in self.findings {
+            text.push_str(&format!("- {}\n", finding));
+        }
+
+        text
+    }
+
+    /// Writes the notes as text and JSON to `INTERNAL_DIR` (default `internal`) and returns the text file's path.
+    pub fn save(&self) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = env::var("INTERNAL_DIR").unwrap_or_else(|_| "internal".to_string());
+        fs::create_dir_all(&dir)?;
+
+        let name = format!("week-{}-{:02}", self.week_start.format("%G"), self.week_number);
+        let path = Path::new(&dir).join(format!("{}.txt", name));
+        fs::write(&path, self.render())?;
+        fs::write(Path::new(&dir).join(format!("{}.json", name)), serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A cached JIRA issue payload, stored as `<KEY>.json` in the cache directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraCacheEntry {
+    pub key: String,
+    pub updated: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub payload: String,
+}
+
+pub struct JiraCache {
+    dir: PathBuf,
+    ttl: Duration,
+    pub offline: bool,
+    record_dir: Option<PathBuf>,
+}
+
+impl JiraCache {
+    pub fn from_env(offline: bool, record_dir: Option<PathBuf>) -> Result<Self, Box<dyn Error>> {
+        let dir = env::var("JIRA_CACHE_DIR").unwrap_or_else(|_| ".jira-cache".to_string());
+        let ttl_hours = env::var("JIRA_CACHE_TTL_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(24);
+
+        Self::new(PathBuf::from(dir), Duration::hours(ttl_hours), offline, record_dir)
+    }
+
+    pub fn new(dir: PathBuf, ttl: Duration, offline: bool, record_dir: Option<PathBuf>) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(&dir)?;
+        if let Some(record_dir) = &record_dir {
+            fs::create_dir_all(record_dir)?;
+        }
+
+        Ok(JiraCache {
+            dir,
+            ttl,
+            offline,
+            record_dir,
+        })
+    }
+
+    fn entry_path(dir: &Path, key: &str) -> PathBuf {
+        dir.join(format!("{}.json", key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<JiraCacheEntry> {
+        let path = Self::entry_path(&self.dir, key);
+        let contents = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("Ignoring unreadable JIRA cache entry {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn is_fresh(&self, entry: &JiraCacheEntry) -> bool {
+        Utc::now() - entry.fetched_at < self.ttl
+    }
+
+    pub fn put(&self, key: &str, updated: Option<String>, payload: &str) -> Result<JiraCacheEntry, Box<dyn Error>> {
+        let entry = JiraCacheEntry {
+            key: key.to_string(),
+            updated,
+            fetched_at: Utc::now(),
+            payload: payload.to_string(),
+        };
+        Self::write_entry(&self.dir, &entry)?;
+        Ok(entry)
+    }
+
+    /// Marks an entry as freshly validated without changing its payload.
+    pub fn touch(&self, entry: &JiraCacheEntry) -> Result<(), Box<dyn Error>> {
+        let mut entry = entry.clone();
+        entry.fetched_at = Utc::now();
+        Self::write_entry(&self.dir, &entry)
+    }
+
+    /// Copies an entry into the fixture directory when recording is enabled.
+    pub fn record(&self, entry: &JiraCacheEntry) {
+        if let Some(record_dir) = &self.record_dir {
+            debug!("Recording JIRA fixture for {}", entry.key);
+            if let Err(e) = Self::write_entry(record_dir, entry) {
+                warn!("Failed to record JIRA fixture for {}: {}", entry.key, e);
+            }
+        }
+    }
+
+    fn write_entry(dir: &Path, entry: &JiraCacheEntry) -> Result<(), Box<dyn Error>> {
+        let path = Self::entry_path(dir, &entry.key);
+        fs::write(path, serde_json::to_string_pretty(entry)?)?;
+        Ok(())
+    }
+}
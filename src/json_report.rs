@@ -0,0 +1,418 @@
+use chrono::{Datelike, Duration, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::actions::PreventativeAction;
+use crate::metrics::{downtime_by_service, LongestIncident};
+use crate::report::{Report, ReportIncident};
+use crate::trends::{Kpi, TRAILING_WEEKS_LONG, TRAILING_WEEKS_SHORT};
+use crate::{extract_jira_key, parse_date, parse_duration_to_minutes, OutageRecord};
+
+/// Version of the JSON output described in `schemas/report.schema.json`. Bump it when a field
+/// is removed or changes meaning; adding fields doesn't need a new version.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+const KPIS: [(&str, Kpi); 6] = [
+    ("incident_count", Kpi::IncidentCount),
+    ("total_downtime_minutes", Kpi::TotalDowntime),
+    ("mean_time_to_resolve_minutes", Kpi::MeanTimeToResolve),
+    ("median_time_to_resolve_minutes", Kpi::MedianTimeToResolve),
+    ("longest_incident_minutes", Kpi::LongestIncident),
+    ("mean_time_to_detect_minutes", Kpi::MeanTimeToDetect),
+];
+
+#[derive(Debug, Serialize)]
+struct WindowJson {
+    week_number: u32,
+    /// ISO week-numbering year
+    year: i32,
+    start_date: String,
+    end_date: String,
+    /// Start of the window, inclusive
+    start: String,
+    /// End of the window, exclusive
+    end: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TrendJson {
+    last_week: Option<f64>,
+    average_4_weeks: Option<f64>,
+    average_13_weeks: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct KpisJson<'a> {
+    incident_count: usize,
+    incidents_by_severity: &'a BTreeMap<String, usize>,
+    total_downtime_minutes: i32,
+    mean_time_to_resolve_minutes: Option<f64>,
+    median_time_to_resolve_minutes: Option<f64>,
+    longest_incident: Option<LongestIncidentJson<'a>>,
+    mean_time_to_detect_minutes: Option<f64>,
+    trends: BTreeMap<&'static str, TrendJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct LongestIncidentJson<'a> {
+    service: &'a str,
+    date: Option<String>,
+    minutes: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct ServiceJson {
+    service: String,
+    incidents: usize,
+    downtime_minutes: i32,
+    /// Share of the week the service was up, in percent
+    availability_percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct ActionJson<'a> {
+    key: &'a str,
+    summary: Option<&'a str>,
+    relationship: &'a str,
+    status: Option<&'a str>,
+    assignee: Option<&'a str>,
+    done: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct IncidentJson<'a> {
+    date: Option<String>,
+    service: &'a str,
+    severity: Option<&'a str>,
+    duration_minutes: Option<i32>,
+    started_at: Option<String>,
+    ended_at: Option<String>,
+    times_estimated: bool,
+    jira_key: Option<&'a str>,
+    ticket_url: Option<&'a str>,
+    cause: &'a str,
+    solution: &'a str,
+    root_cause: Option<&'a str>,
+    preventative_measures: Option<&'a str>,
+    recurrence: Option<&'a str>,
+    narrative: Option<&'a str>,
+    preventative_actions: Vec<ActionJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct CarriedOverJson<'a> {
+    incident_date: Option<String>,
+    incident_service: &'a str,
+    incident_jira_key: Option<String>,
+    #[serde(flatten)]
+    action: ActionJson<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportJson<'a> {
+    schema_version: u32,
+    title: &'static str,
+    ai_generated: bool,
+    window: WindowJson,
+    summary: Option<&'a str>,
+    kpis: KpisJson<'a>,
+    services: Vec<ServiceJson>,
+    incidents: Vec<IncidentJson<'a>>,
+    carried_over_actions: Vec<CarriedOverJson<'a>>,
+}
+
+fn timestamp(time: NaiveDateTime) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn iso_date(date: &str) -> Option<String> {
+    parse_date(date).map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+fn non_empty(text: &str) -> Option<&str> {
+    let text = text.trim();
+    (!text.is_empty()).then_some(text)
+}
+
+fn action_json(action: &PreventativeAction) -> ActionJson<'_> {
+    ActionJson {
+        key: &action.key,
+        summary: action.summary.as_deref().map(str::trim),
+        relationship: &action.relationship,
+        status: action.status.as_deref(),
+        assignee: action.assignee.as_deref(),
+        done: action.done,
+    }
+}
+
+fn longest_incident_json(longest: &LongestIncident) -> LongestIncidentJson<'_> {
+    LongestIncidentJson {
+        service: longest.service.trim(),
+        date: iso_date(&longest.date),
+        minutes: longest.minutes,
+    }
+}
+
+fn kpis_json<'a>(report: &Report<'a>) -> KpisJson<'a> {
+    let kpis = report.kpis;
+    let trends = KPIS
+        .iter()
+        .map(|(name, kpi)| {
+            let trend = TrendJson {
+                last_week: report.trends.last_week(*kpi),
+                average_4_weeks: report.trends.trailing_average(*kpi, TRAILING_WEEKS_SHORT),
+                // Only once there's more history than the short average covers, as in the text report
+                average_13_weeks: (report.trends.previous_weeks.len() > TRAILING_WEEKS_SHORT)
+                    .then(|| report.trends.trailing_average(*kpi, TRAILING_WEEKS_LONG))
+                    .flatten(),
+            };
+            (*name, trend)
+        })
+        .collect();
+
+    KpisJson {
+        incident_count: kpis.incident_count,
+        incidents_by_severity: &kpis.by_severity,
+        total_downtime_minutes: kpis.total_downtime_minutes,
+        mean_time_to_resolve_minutes: kpis.mean_time_to_resolve,
+        median_time_to_resolve_minutes: kpis.median_time_to_resolve,
+        longest_incident: kpis.longest_incident.as_ref().map(longest_incident_json),
+        mean_time_to_detect_minutes: kpis.mean_time_to_detect,
+        trends,
+    }
+}
+
+fn services_json(report: &Report) -> Vec<ServiceJson> {
+    let window_minutes = ((report.week_end - report.week_start).num_days() + 1) as f64 * 24.0 * 60.0;
+    downtime_by_service(report.incidents.iter().map(|incident| incident.record))
+        .into_iter()
+        .map(|(service, downtime_minutes)| {
+            let incidents = report
+                .incidents
+                .iter()
+                .filter(|incident| incident.record.service.trim() == service)
+                .count();
+            let availability = (100.0 * (1.0 - downtime_minutes as f64 / window_minutes)).max(0.0);
+            ServiceJson {
+                service,
+                incidents,
+                downtime_minutes,
+                availability_percent: (availability * 1000.0).round() / 1000.0,
+            }
+        })
+        .collect()
+}
+
+fn incident_json<'a>(report: &'a Report, incident: &'a ReportIncident) -> IncidentJson<'a> {
+    let record = incident.record;
+    let actions = report
+        .actions
+        .incidents
+        .iter()
+        .find(|(action_record, _)| std::ptr::eq(*action_record, record))
+        .map(|(_, actions)| actions.iter().map(action_json).collect())
+        .unwrap_or_default();
+
+    IncidentJson {
+        date: iso_date(&record.date),
+        service: record.service.trim(),
+        severity: non_empty(&record.severity),
+        duration_minutes: non_empty(&record.duration).map(parse_duration_to_minutes),
+        started_at: incident.window.map(|window| timestamp(window.start)),
+        ended_at: incident.window.map(|window| timestamp(window.end)),
+        times_estimated: incident.window.is_none_or(|window| window.estimated),
+        jira_key: incident.jira_key.as_deref(),
+        ticket_url: incident.ticket_url(),
+        cause: record.cause.trim(),
+        solution: record.solution.trim(),
+        root_cause: incident.rca.rca.as_deref().or(incident.rca.related_line.as_deref()),
+        preventative_measures: incident.rca.preventative_measures.as_deref(),
+        recurrence: incident.recurrence,
+        narrative: incident.narrative,
+        preventative_actions: actions,
+    }
+}
+
+fn carried_over_json<'a>(record: &'a OutageRecord, action: &'a PreventativeAction) -> CarriedOverJson<'a> {
+    CarriedOverJson {
+        incident_date: iso_date(&record.date),
+        incident_service: record.service.trim(),
+        incident_jira_key: extract_jira_key(&record.ticket),
+        action: action_json(action),
+    }
+}
+
+/// Renders the report as JSON for dashboards, following `schemas/report.schema.json`.
+pub fn render_json(report: &Report) -> Result<String, serde_json::Error> {
+    let json = ReportJson {
+        schema_version: REPORT_SCHEMA_VERSION,
+        title: report.title(),
+        ai_generated: report.ai.is_some(),
+        window: WindowJson {
+            week_number: report.week_number,
+            year: report.week_start.iso_week().year(),
+            start_date: report.week_start.format("%Y-%m-%d").to_string(),
+            end_date: report.week_end.format("%Y-%m-%d").to_string(),
+            start: timestamp(report.week_start.and_hms_opt(0, 0, 0).expect("midnight is valid")),
+            end: timestamp((report.week_end + Duration::days(1)).and_hms_opt(0, 0, 0).expect("midnight is valid")),
+        },
+        summary: report.ai.and_then(|ai| ai.summary.as_deref()),
+        kpis: kpis_json(report),
+        services: services_json(report),
+        incidents: report.incidents.iter().map(|incident| incident_json(report, incident)).collect(),
+        carried_over_actions: report
+            .actions
+            .carried_over
+            .iter()
+            .map(|(record, action)| carried_over_json(record, action))
+            .collect(),
+    };
+    serde_json::to_string_pretty(&json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::collect_actions;
+    use crate::ai_report::AiReport;
+    use crate::report::fixture::{outage, ReportFixture};
+    use crate::JiraIssue;
+    use serde_json::Value;
+
+    /// Checks the parts of JSON Schema the report schema uses and returns where `value` breaks it.
+    fn violations(value: &Value, schema: &Value, root: &Value, path: &str) -> Vec<String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            return violations(value, &root["$defs"][name], root, path);
+        }
+
+        let mut found = Vec::new();
+        for part in schema["allOf"].as_array().into_iter().flatten() {
+            found.extend(violations(value, part, root, path));
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            if options.iter().all(|option| !violations(value, option, root, path).is_empty()) {
+                found.push(format!("{}: matches none of oneOf", path));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                found.push(format!("{}: expected {}", path, expected));
+            }
+        }
+        let types: Vec<&str> = match &schema["type"] {
+            Value::String(name) => vec![name],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let has_type = |name: &str| match name {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => false,
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(name)) {
+            found.push(format!("{}: {} is not {:?}", path, value, types));
+        }
+        if let (Some(number), Some(minimum)) = (value.as_f64(), schema["minimum"].as_f64()) {
+            if number < minimum {
+                found.push(format!("{}: below {}", path, minimum));
+            }
+        }
+        if let (Some(number), Some(maximum)) = (value.as_f64(), schema["maximum"].as_f64()) {
+            if number > maximum {
+                found.push(format!("{}: above {}", path, maximum));
+            }
+        }
+
+        if let Some(object) = value.as_object() {
+            for key in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    found.push(format!("{}: missing {}", path, key));
+                }
+            }
+            for (key, field) in object {
+                let field_path = format!("{}.{}", path, key);
+                if let Some(property) = schema["properties"].get(key) {
+                    found.extend(violations(field, property, root, &field_path));
+                } else if schema["additionalProperties"].is_object() {
+                    found.extend(violations(field, &schema["additionalProperties"], root, &field_path));
+                }
+            }
+        }
+        if let Some(items) = value.as_array().filter(|_| schema["items"].is_object()) {
+            for (index, item) in items.iter().enumerate() {
+                found.extend(violations(item, &schema["items"], root, &format!("{}[{}]", path, index)));
+            }
+        }
+        found
+    }
+
+    fn linked(key: &str, status: &str, category: &str) -> Value {
+        serde_json::json!({
+            "key": key,
+            "fields": {
+                "summary": format!("Follow up {}", key),
+                "status": { "name": status, "statusCategory": { "key": category } }
+            }
+        })
+    }
+
+    fn jira(key: &str, fields: Value) -> (String, JiraIssue) {
+        let payload = serde_json::json!({ "key": key, "fields": fields }).to_string();
+        (key.to_string(), JiraIssue::from_payload(&payload).unwrap())
+    }
+
+    #[test]
+    fn output_follows_the_schema() {
+        let schema: Value = serde_json::from_str(include_str!("../schemas/report.schema.json")).unwrap();
+        let mut fixture = ReportFixture::new(vec![
+            outage(1, "Nginx failed"),
+            OutageRecord {
+                date: "not a date".to_string(),
+                duration: String::new(),
+                severity: String::new(),
+                ..outage(2, "Disk full")
+            },
+        ]);
+        let earlier = OutageRecord { date: "28/Sep/26".to_string(), ..outage(3, "Nginx failed") };
+        let causes = serde_json::json!({ "inward": "is caused by", "outward": "causes" });
+        fixture.jira_details.extend([
+            jira("OPS-1", serde_json::json!({
+                "description": "Outage 10:05 - 10:20\nRCA: The config was reverted",
+                "issuelinks": [{ "type": causes, "inwardIssue": linked("DEV-1", "Done", "done") }],
+                "subtasks": [linked("OPS-11", "To Do", "new")]
+            })),
+            jira("OPS-3", serde_json::json!({
+                "issuelinks": [{ "type": causes, "outwardIssue": linked("DEV-3", "In Progress", "indeterminate") }]
+            })),
+        ]);
+        let ai = AiReport {
+            text: String::new(),
+            narratives: vec![Some("Sales-I US was down for 15 minutes.".to_string()), None],
+            summary: Some("Two incidents this week.".to_string()),
+            recommendations: Vec::new(),
+        };
+        let report = Report {
+            actions: collect_actions(&fixture.outages, std::slice::from_ref(&earlier), &fixture.jira_details),
+            ai: Some(&ai),
+            ..fixture.report()
+        };
+
+        let json: Value = serde_json::from_str(&render_json(&report).unwrap()).unwrap();
+        assert_eq!(violations(&json, &schema, &schema, "$"), Vec::<String>::new());
+        // The optional parts were filled in, so their schema was checked too
+        assert_eq!(json["incidents"][0]["preventative_actions"].as_array().unwrap().len(), 2);
+        assert_eq!(json["carried_over_actions"][0]["key"], "DEV-3");
+        assert!(json["incidents"][1]["date"].is_null());
+
+        let mut broken = json.clone();
+        broken["window"].as_object_mut().unwrap().remove("year");
+        broken["services"][0]["availability_percent"] = serde_json::json!(120);
+        assert_eq!(violations(&broken, &schema, &schema, "$").len(), 2);
+    }
+}
@@ -0,0 +1,998 @@
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::structured::OutputMode;
+
+/// A single prompt to send to a model, independent of the provider's wire format.
+#[derive(Debug, Clone)]
+pub struct LlmRequest {
+    pub system_prompt: String,
+    pub prompt: String,
+    pub temperature: f32,
+    pub max_tokens: i32,
+    /// JSON schema the response must follow, for providers that can enforce one
+    pub json_schema: Option<serde_json::Value>,
+}
+
+/// One parsed line of a streamed response.
+#[derive(Debug, PartialEq)]
+pub enum StreamEvent {
+    Token(String),
+    Done,
+}
+
+/// Builds provider-specific HTTP requests and parses their responses.
+/// Sending is shared so every provider gets the same timeouts and error handling.
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &LlmConfig,
+        request: &LlmRequest,
+        stream: bool,
+    ) -> reqwest::RequestBuilder;
+    fn parse_response(&self, response_text: &str) -> Result<String, Box<dyn Error>>;
+    /// Parses a single line of a streamed response. Lines that carry no content return `None`.
+    fn parse_stream_line(&self, line: &str) -> Result<Option<StreamEvent>, Box<dyn Error>>;
+}
+
+/// A raw response body as received, kept verbatim for snapshots.
+#[derive(Debug, Clone)]
+pub struct LlmResponse {
+    pub raw: String,
+    pub streamed: bool,
+}
+
+impl LlmResponse {
+    pub fn content(&self, provider: &dyn LlmProvider) -> Result<String, Box<dyn Error>> {
+        if self.streamed {
+            parse_stream_transcript(provider, &self.raw)
+        } else {
+            provider.parse_response(&self.raw)
+        }
+    }
+}
+
+/// Reassembles the content of a streamed response from its raw transcript.
+pub fn parse_stream_transcript(provider: &dyn LlmProvider, raw: &str) -> Result<String, Box<dyn Error>> {
+    let mut content = String::new();
+    for line in raw.lines() {
+        match provider.parse_stream_line(line)? {
+            Some(StreamEvent::Token(token)) => content.push_str(&token),
+            Some(StreamEvent::Done) => break,
+            None => {}
+        }
+    }
+
+    if content.is_empty() {
+        Err(format!("No response from {} provider", provider.name()).into())
+    } else {
+        Ok(content)
+    }
+}
+
+/// How streamed output is shown on stderr while waiting for the model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressStyle {
+    Tokens,
+    Spinner,
+    None,
+}
+
+impl ProgressStyle {
+    fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "tokens" => Ok(ProgressStyle::Tokens),
+            "spinner" => Ok(ProgressStyle::Spinner),
+            "none" | "off" => Ok(ProgressStyle::None),
+            other => Err(format!("Unknown LLM progress style '{}'", other).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    /// LM Studio, vLLM, llama.cpp server and anything else speaking `/v1/chat/completions`
+    OpenAi,
+    Ollama,
+    Anthropic,
+}
+
+impl ProviderKind {
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "openai" | "lmstudio" | "lm-studio" | "vllm" | "llamacpp" | "llama.cpp" => Ok(ProviderKind::OpenAi),
+            "ollama" => Ok(ProviderKind::Ollama),
+            "anthropic" => Ok(ProviderKind::Anthropic),
+            other => Err(format!("Unknown LLM provider '{}'", other).into()),
+        }
+    }
+
+    fn default_url(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "http://localhost:1234/v1/chat/completions",
+            ProviderKind::Ollama => "http://localhost:11434/api/chat",
+            ProviderKind::Anthropic => "https://api.anthropic.com/v1/messages",
+        }
+    }
+
+    pub fn provider(&self) -> Box<dyn LlmProvider> {
+        match self {
+            ProviderKind::OpenAi => Box::new(OpenAiProvider),
+            ProviderKind::Ollama => Box::new(OllamaProvider),
+            ProviderKind::Anthropic => Box::new(AnthropicProvider),
+        }
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderKind::OpenAi => write!(f, "openai"),
+            ProviderKind::Ollama => write!(f, "ollama"),
+            ProviderKind::Anthropic => write!(f, "anthropic"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    pub provider: ProviderKind,
+    pub url: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    pub temperature: f32,
+    pub max_tokens: i32,
+    pub stream: bool,
+    pub output_mode: OutputMode,
+    pub progress: ProgressStyle,
+    /// How long to wait for the first token, including the time the model spends on the prompt
+    pub first_token_timeout: Duration,
+    /// How long the response may stall between chunks once it has started
+    pub idle_timeout: Duration,
+    /// How long a request may take in total when the response isn't streamed
+    pub timeout: Duration,
+    /// Maximum number of requests in flight when generating incident narratives
+    pub concurrency: usize,
+}
+
+fn var_secs(var: &impl Fn(&str) -> Option<String>, name: &str, default: u64) -> Duration {
+    Duration::from_secs(var(name).and_then(|v| v.parse().ok()).unwrap_or(default))
+}
+
+impl LlmConfig {
+    /// Reads `LLM_*` variables, falling back to the older `LM_STUDIO_*` ones.
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        Self::from_vars(|name| env::var(name).ok(), std::io::stderr().is_terminal())
+    }
+
+    /// Builds the config from variables looked up with `var`. `interactive` picks the default progress style.
+    fn from_vars(var: impl Fn(&str) -> Option<String>, interactive: bool) -> Result<Self, Box<dyn Error>> {
+        let provider = match var("LLM_PROVIDER") {
+            Some(name) => ProviderKind::parse(&name)?,
+            None => ProviderKind::OpenAi,
+        };
+
+        // The LM_STUDIO_* variables only describe an OpenAI-compatible server
+        let legacy = |name: &str| match provider {
+            ProviderKind::OpenAi => var(name),
+            _ => None,
+        };
+
+        let url = var("LLM_URL")
+            .or_else(|| legacy("LM_STUDIO_URL"))
+            .unwrap_or_else(|| provider.default_url().to_string());
+
+        let model = var("LLM_MODEL")
+            .or_else(|| legacy("LM_STUDIO_MODEL"))
+            .unwrap_or_else(|| "local-model".to_string());
+
+        let api_key = var("LLM_API_KEY").filter(|key| !key.is_empty());
+        if provider == ProviderKind::Anthropic && api_key.is_none() {
+            return Err("LLM_API_KEY must be set for the anthropic provider".into());
+        }
+
+        let temperature = var("LLM_TEMPERATURE")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.3);
+
+        let max_tokens = var("LLM_MAX_TOKENS")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(4000);
+
+        let stream = var("LLM_STREAM").map(|v| v == "true").unwrap_or(true);
+
+        let output_mode = match var("LLM_OUTPUT_MODE") {
+            Some(mode) => OutputMode::parse(&mode)?,
+            None => OutputMode::Structured,
+        };
+
+        // Echoing tokens only makes sense when someone is watching
+        let progress = match var("LLM_PROGRESS") {
+            Some(style) => ProgressStyle::parse(&style)?,
+            None if interactive => ProgressStyle::Tokens,
+            None => ProgressStyle::None,
+        };
+
+        Ok(LlmConfig {
+            provider,
+            url,
+            model,
+            api_key,
+            temperature,
+            max_tokens,
+            stream,
+            output_mode,
+            progress,
+            first_token_timeout: var_secs(&var, "LLM_FIRST_TOKEN_TIMEOUT_SECS", 180),
+            idle_timeout: var_secs(&var, "LLM_IDLE_TIMEOUT_SECS", 60),
+            timeout: var_secs(&var, "LLM_TIMEOUT_SECS", 300),
+            concurrency: var("LLM_CONCURRENCY")
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &usize| n > 0)
+                .unwrap_or(4),
+        })
+    }
+
+    pub fn request(&self, system_prompt: &str, prompt: &str, json_schema: Option<serde_json::Value>) -> LlmRequest {
+        LlmRequest {
+            system_prompt: system_prompt.to_string(),
+            prompt: prompt.to_string(),
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            json_schema,
+        }
+    }
+}
+
+/// Sends the prompt to the configured provider and returns the raw response body. A streamed response may take
+/// at most `first_token_timeout` until the first content and `idle_timeout` between chunks after that, any other
+/// at most `timeout` in total.
+pub async fn call_llm(config: &LlmConfig, request: &LlmRequest) -> Result<LlmResponse, Box<dyn Error>> {
+    let provider = config.provider.provider();
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .build()?;
+
+    debug!(
+        "Sending {} request to {} provider at {}",
+        if config.stream { "streaming" } else { "non-streaming" },
+        provider.name(),
+        config.url
+    );
+    debug!("LLM request prompt:\n{}", request.prompt);
+
+    let send = provider.build_request(&client, config, request, config.stream).send();
+    if !config.stream {
+        // Nothing arrives before the whole response is ready, so only the total time can be limited
+        let receive = async {
+            let response = successful(provider.as_ref(), send.await?).await?;
+            Ok(LlmResponse { raw: response.text().await?, streamed: false })
+        };
+        return tokio::time::timeout(config.timeout, receive)
+            .await
+            .map_err(|_| format!("{} did not respond within {}s", provider.name(), config.timeout.as_secs()))?;
+    }
+
+    let started = Instant::now();
+    let first_token_deadline = started + config.first_token_timeout;
+    let response = tokio::time::timeout(config.first_token_timeout, send)
+        .await
+        .map_err(|_| format!("{} did not respond within {}s", provider.name(), config.first_token_timeout.as_secs()))??;
+    let mut response = successful(provider.as_ref(), response).await?;
+
+    let mut progress = Progress::new(config.progress);
+    let mut raw: Vec<u8> = Vec::new();
+    let mut lines = LineBuffer::default();
+    let mut got_first_token = false;
+
+    loop {
+        let wait = if got_first_token {
+            config.idle_timeout
+        } else {
+            first_token_deadline.saturating_duration_since(Instant::now())
+        };
+
+        let chunk = match tokio::time::timeout(wait, response.chunk()).await {
+            Ok(chunk) => chunk?,
+            Err(_) if got_first_token => {
+                progress.finish();
+                return Err(format!(
+                    "{} stalled for more than {}s mid-response",
+                    provider.name(),
+                    config.idle_timeout.as_secs()
+                )
+                .into());
+            }
+            Err(_) => {
+                progress.finish();
+                return Err(format!(
+                    "{} sent no tokens within {}s",
+                    provider.name(),
+                    config.first_token_timeout.as_secs()
+                )
+                .into());
+            }
+        };
+
+        let Some(chunk) = chunk else {
+            break;
+        };
+        raw.extend_from_slice(&chunk);
+
+        for line in lines.push(&chunk) {
+            match provider.parse_stream_line(&line)? {
+                Some(StreamEvent::Token(token)) => {
+                    if !got_first_token {
+                        debug!("First token after {:.1}s", started.elapsed().as_secs_f32());
+                    }
+                    got_first_token = true;
+                    progress.token(&token);
+                }
+                Some(StreamEvent::Done) | None => {}
+            }
+        }
+    }
+
+    progress.finish();
+    debug!("LLM response complete after {:.1}s", started.elapsed().as_secs_f32());
+
+    Ok(LlmResponse {
+        raw: String::from_utf8(raw)?,
+        streamed: true,
+    })
+}
+
+/// Returns the response if its status is a success, otherwise an error with the body the provider sent.
+async fn successful(
+    provider: &dyn LlmProvider,
+    response: reqwest::Response,
+) -> Result<reqwest::Response, Box<dyn Error>> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+    Err(format!("{} API error {}: {}", provider.name(), status, error_text).into())
+}
+
+/// Collects streamed bytes into lines. A chunk can end mid-line or mid-character, so lines are only decoded
+/// once complete.
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+        while let Some(newline) = self.pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=newline).collect();
+            lines.push(String::from_utf8_lossy(&line).trim_end().to_string());
+        }
+        lines
+    }
+}
+
+/// Sends several requests with at most `config.concurrency` in flight and returns the results in request order.
+/// Token echo would interleave between parallel streams, so progress is reported per completed request instead.
+pub async fn call_llm_batch(config: &LlmConfig, requests: Vec<LlmRequest>) -> Vec<Result<LlmResponse, Box<dyn Error>>> {
+    let total = requests.len();
+    let mut task_config = config.clone();
+    if config.concurrency > 1 {
+        task_config.progress = ProgressStyle::None;
+    }
+    let task_config = Arc::new(task_config);
+    let semaphore = Arc::new(Semaphore::new(config.concurrency));
+    let mut tasks = JoinSet::new();
+
+    for (index, request) in requests.into_iter().enumerate() {
+        let config = Arc::clone(&task_config);
+        let semaphore = Arc::clone(&semaphore);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            // Box<dyn Error> isn't Send, so errors cross the task boundary as strings
+            let result = call_llm(&config, &request).await.map_err(|e| e.to_string());
+            (index, result)
+        });
+    }
+
+    let mut results: Vec<Option<Result<LlmResponse, Box<dyn Error>>>> = (0..total).map(|_| None).collect();
+    let mut completed = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, result)) => {
+                completed += 1;
+                if config.concurrency > 1 && config.progress != ProgressStyle::None {
+                    eprintln!("LLM requests: {}/{} done", completed, total);
+                }
+                results[index] = Some(result.map_err(Into::into));
+            }
+            Err(e) => debug!("LLM task failed to complete: {}", e),
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Err("LLM request task panicked".into())))
+        .collect()
+}
+
+struct Progress {
+    style: ProgressStyle,
+    tokens: usize,
+    started: Instant,
+}
+
+impl Progress {
+    fn new(style: ProgressStyle) -> Self {
+        Progress {
+            style,
+            tokens: 0,
+            started: Instant::now(),
+        }
+    }
+
+    fn token(&mut self, token: &str) {
+        self.tokens += 1;
+        let mut stderr = std::io::stderr();
+        match self.style {
+            ProgressStyle::Tokens => {
+                let _ = write!(stderr, "{}", token);
+            }
+            ProgressStyle::Spinner => {
+                const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+                let _ = write!(
+                    stderr,
+                    "\r{} Receiving response: {} tokens, {}s",
+                    FRAMES[self.tokens % FRAMES.len()],
+                    self.tokens,
+                    self.started.elapsed().as_secs()
+                );
+            }
+            ProgressStyle::None => return,
+        }
+        let _ = stderr.flush();
+    }
+
+    fn finish(&mut self) {
+        if self.style != ProgressStyle::None && self.tokens > 0 {
+            eprintln!();
+        }
+        self.tokens = 0;
+    }
+}
+
+/// Returns the JSON payload of a server-sent event `data:` line.
+fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim)
+}
+
+fn truncate_for_error(text: &str) -> &str {
+    let mut end = text.len().min(500);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+fn chat_messages(request: &LlmRequest) -> Vec<ChatMessage> {
+    vec![
+        ChatMessage {
+            role: "system".to_string(),
+            content: request.system_prompt.clone(),
+        },
+        ChatMessage {
+            role: "user".to_string(),
+            content: request.prompt.clone(),
+        },
+    ]
+}
+
+pub struct OpenAiProvider;
+
+#[derive(Debug, Serialize)]
+struct OpenAiRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    temperature: f32,
+    max_tokens: i32,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResponse {
+    choices: Vec<OpenAiChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChoice {
+    message: OpenAiMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiMessage {
+    content: String,
+}
+
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "OpenAI-compatible"
+    }
+
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &LlmConfig,
+        request: &LlmRequest,
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        let body = OpenAiRequest {
+            model: config.model.clone(),
+            messages: chat_messages(request),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+            stream,
+            response_format: request.json_schema.as_ref().map(|schema| {
+                serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "weekly_report", "strict": true, "schema": schema }
+                })
+            }),
+        };
+
+        let builder = client.post(&config.url).json(&body);
+        match &config.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    fn parse_response(&self, response_text: &str) -> Result<String, Box<dyn Error>> {
+        let response: OpenAiResponse = serde_json::from_str(response_text).map_err(|e| {
+            format!("Failed to parse {} response: {}. Response: {}", self.name(), e, truncate_for_error(response_text))
+        })?;
+
+        match response.choices.first() {
+            Some(choice) => Ok(choice.message.content.clone()),
+            None => Err(format!("No response from {} provider", self.name()).into()),
+        }
+    }
+
+    fn parse_stream_line(&self, line: &str) -> Result<Option<StreamEvent>, Box<dyn Error>> {
+        let Some(data) = sse_data(line) else {
+            return Ok(None);
+        };
+        if data == "[DONE]" {
+            return Ok(Some(StreamEvent::Done));
+        }
+
+        let chunk: OpenAiStreamChunk = serde_json::from_str(data)?;
+        let token = chunk
+            .choices
+            .first()
+            .and_then(|choice| choice.delta.content.clone())
+            .filter(|token| !token.is_empty());
+        Ok(token.map(StreamEvent::Token))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChunk {
+    choices: Vec<OpenAiStreamChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChoice {
+    delta: OpenAiDelta,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiDelta {
+    content: Option<String>,
+}
+
+pub struct OllamaProvider;
+
+#[derive(Debug, Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
+    options: OllamaOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    temperature: f32,
+    num_predict: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaResponse {
+    message: OllamaMessage,
+    #[serde(default)]
+    done: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaMessage {
+    content: String,
+}
+
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "Ollama"
+    }
+
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &LlmConfig,
+        request: &LlmRequest,
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        let body = OllamaRequest {
+            model: config.model.clone(),
+            messages: chat_messages(request),
+            stream,
+            format: request.json_schema.clone(),
+            options: OllamaOptions {
+                temperature: request.temperature,
+                num_predict: request.max_tokens,
+            },
+        };
+
+        client.post(&config.url).json(&body)
+    }
+
+    fn parse_response(&self, response_text: &str) -> Result<String, Box<dyn Error>> {
+        let response: OllamaResponse = serde_json::from_str(response_text).map_err(|e| {
+            format!("Failed to parse {} response: {}. Response: {}", self.name(), e, truncate_for_error(response_text))
+        })?;
+        Ok(response.message.content)
+    }
+
+    fn parse_stream_line(&self, line: &str) -> Result<Option<StreamEvent>, Box<dyn Error>> {
+        // Ollama streams newline-delimited JSON objects rather than server-sent events
+        if line.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let chunk: OllamaResponse = serde_json::from_str(line)?;
+        if chunk.done {
+            Ok(Some(StreamEvent::Done))
+        } else if chunk.message.content.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(StreamEvent::Token(chunk.message.content)))
+        }
+    }
+}
+
+pub struct AnthropicProvider;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+#[derive(Debug, Serialize)]
+struct AnthropicRequest {
+    model: String,
+    system: String,
+    messages: Vec<ChatMessage>,
+    max_tokens: i32,
+    temperature: f32,
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicStreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    delta: Option<AnthropicDelta>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicDelta {
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicResponse {
+    content: Vec<AnthropicContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicContent {
+    #[serde(rename = "type")]
+    content_type: String,
+    #[serde(default)]
+    text: String,
+}
+
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "Anthropic"
+    }
+
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &LlmConfig,
+        request: &LlmRequest,
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        // The Messages API takes the system prompt as a top-level field rather than a message.
+        // It has no response schema option, so structured output relies on the prompt alone
+        let body = AnthropicRequest {
+            model: config.model.clone(),
+            system: request.system_prompt.clone(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: request.prompt.clone(),
+            }],
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            stream,
+        };
+
+        client
+            .post(&config.url)
+            .header("x-api-key", config.api_key.clone().unwrap_or_default())
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body)
+    }
+
+    fn parse_response(&self, response_text: &str) -> Result<String, Box<dyn Error>> {
+        let response: AnthropicResponse = serde_json::from_str(response_text).map_err(|e| {
+            format!("Failed to parse {} response: {}. Response: {}", self.name(), e, truncate_for_error(response_text))
+        })?;
+
+        let text = response
+            .content
+            .iter()
+            .filter(|block| block.content_type == "text")
+            .map(|block| block.text.as_str())
+            .collect::<String>();
+
+        if text.is_empty() {
+            Err(format!("No response from {} provider", self.name()).into())
+        } else {
+            Ok(text)
+        }
+    }
+
+    fn parse_stream_line(&self, line: &str) -> Result<Option<StreamEvent>, Box<dyn Error>> {
+        // `event:` lines repeat the type that is also in the data payload
+        let Some(data) = sse_data(line) else {
+            return Ok(None);
+        };
+
+        let event: AnthropicStreamEvent = serde_json::from_str(data)?;
+        match event.event_type.as_str() {
+            "content_block_delta" => Ok(event
+                .delta
+                .and_then(|delta| delta.text)
+                .filter(|text| !text.is_empty())
+                .map(StreamEvent::Token)),
+            "message_stop" => Ok(Some(StreamEvent::Done)),
+            "error" => Err(format!(
+                "{} stream error: {}",
+                self.name(),
+                event.error.map(|e| e.to_string()).unwrap_or_default()
+            )
+            .into()),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(text: &str) -> Option<StreamEvent> {
+        Some(StreamEvent::Token(text.to_string()))
+    }
+
+    fn config(vars: &[(&str, &str)]) -> Result<LlmConfig, Box<dyn Error>> {
+        LlmConfig::from_vars(
+            |name| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string()),
+            false,
+        )
+    }
+
+    /// Builds the request the provider would send and returns it with its JSON body.
+    fn sent(config: &LlmConfig, json_schema: Option<serde_json::Value>) -> (reqwest::Request, serde_json::Value) {
+        let request = config.request("You write incident reports.", "Summarize SALES-101.", json_schema);
+        let built = config
+            .provider
+            .provider()
+            .build_request(&reqwest::Client::new(), config, &request, true)
+            .build()
+            .unwrap();
+        let body = serde_json::from_slice(built.body().unwrap().as_bytes().unwrap()).unwrap();
+        (built, body)
+    }
+
+    #[test]
+    fn selects_the_provider_and_its_defaults() {
+        let default = config(&[]).unwrap();
+        assert_eq!(default.provider, ProviderKind::OpenAi);
+        assert_eq!(default.url, "http://localhost:1234/v1/chat/completions");
+        assert_eq!(default.model, "local-model");
+        assert_eq!(default.progress, ProgressStyle::None);
+        assert_eq!(default.timeout, Duration::from_secs(300));
+
+        let ollama = config(&[("LLM_PROVIDER", "ollama"), ("LLM_MODEL", "llama3")]).unwrap();
+        assert_eq!(ollama.url, "http://localhost:11434/api/chat");
+        assert_eq!(ollama.model, "llama3");
+        assert_eq!(config(&[("LLM_PROVIDER", "lm-studio")]).unwrap().provider, ProviderKind::OpenAi);
+        assert!(config(&[("LLM_PROVIDER", "gemini")]).is_err());
+    }
+
+    #[test]
+    fn lm_studio_variables_only_apply_to_openai_compatible_servers() {
+        let legacy = [("LM_STUDIO_URL", "http://studio:1234/v1/chat/completions"), ("LM_STUDIO_MODEL", "qwen")];
+        let openai = config(&legacy).unwrap();
+        assert_eq!(openai.url, "http://studio:1234/v1/chat/completions");
+        assert_eq!(openai.model, "qwen");
+
+        let newer = config(&[legacy[0], legacy[1], ("LLM_MODEL", "mistral")]).unwrap();
+        assert_eq!(newer.model, "mistral");
+
+        let ollama = config(&[legacy[0], legacy[1], ("LLM_PROVIDER", "ollama")]).unwrap();
+        assert_eq!(ollama.url, "http://localhost:11434/api/chat");
+        assert_eq!(ollama.model, "local-model");
+    }
+
+    #[test]
+    fn anthropic_requires_an_api_key() {
+        assert!(config(&[("LLM_PROVIDER", "anthropic")]).is_err());
+        assert!(config(&[("LLM_PROVIDER", "anthropic"), ("LLM_API_KEY", "")]).is_err());
+        let anthropic = config(&[("LLM_PROVIDER", "anthropic"), ("LLM_API_KEY", "sk-test")]).unwrap();
+        assert_eq!(anthropic.url, "https://api.anthropic.com/v1/messages");
+    }
+
+    #[test]
+    fn builds_openai_requests() {
+        let config = config(&[("LLM_API_KEY", "sk-test")]).unwrap();
+        let (request, body) = sent(&config, Some(serde_json::json!({ "type": "object" })));
+
+        assert_eq!(request.url().as_str(), "http://localhost:1234/v1/chat/completions");
+        assert_eq!(request.headers()["authorization"], "Bearer sk-test");
+        assert_eq!(body["model"], "local-model");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["max_tokens"], 4000);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][0]["content"], "You write incident reports.");
+        assert_eq!(body["messages"][1]["role"], "user");
+        assert_eq!(body["messages"][1]["content"], "Summarize SALES-101.");
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["schema"]["type"], "object");
+
+        let (request, body) = sent(&LlmConfig { api_key: None, ..config }, None);
+        assert!(request.headers().get("authorization").is_none());
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn builds_ollama_requests() {
+        let config = config(&[("LLM_PROVIDER", "ollama")]).unwrap();
+        let (request, body) = sent(&config, Some(serde_json::json!({ "type": "object" })));
+
+        assert_eq!(request.url().as_str(), "http://localhost:11434/api/chat");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "Summarize SALES-101.");
+        assert_eq!(body["format"]["type"], "object");
+        assert_eq!(body["options"]["num_predict"], 4000);
+        assert!(body.get("max_tokens").is_none());
+    }
+
+    #[test]
+    fn builds_anthropic_requests() {
+        let config = config(&[("LLM_PROVIDER", "anthropic"), ("LLM_API_KEY", "sk-test")]).unwrap();
+        let (request, body) = sent(&config, Some(serde_json::json!({ "type": "object" })));
+
+        assert_eq!(request.headers()["x-api-key"], "sk-test");
+        assert_eq!(request.headers()["anthropic-version"], ANTHROPIC_VERSION);
+        assert!(request.headers().get("authorization").is_none());
+        assert_eq!(body["system"], "You write incident reports.");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["messages"][0]["role"], "user");
+        assert_eq!(body["max_tokens"], 4000);
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn parses_recorded_responses() {
+        let openai = r#"{"id":"chatcmpl-1","object":"chat.completion","model":"local-model",
+            "choices":[{"index":0,"message":{"role":"assistant","content":"Sales-I US was down."},
+            "finish_reason":"stop"}],
+            "usage":{"prompt_tokens":20,"completion_tokens":6,"total_tokens":26}}"#;
+        assert_eq!(OpenAiProvider.parse_response(openai).unwrap(), "Sales-I US was down.");
+        assert!(OpenAiProvider.parse_response(r#"{"choices":[]}"#).is_err());
+
+        let ollama = r#"{"model":"llama3","created_at":"2026-10-12T10:00:00Z",
+            "message":{"role":"assistant","content":"Sales-I US was down."},"done":true,"total_duration":1200}"#;
+        assert_eq!(OllamaProvider.parse_response(ollama).unwrap(), "Sales-I US was down.");
+
+        let anthropic = r#"{"id":"msg_1","type":"message","role":"assistant","model":"claude",
+            "content":[{"type":"text","text":"Sales-I US "},{"type":"text","text":"was down."}],
+            "stop_reason":"end_turn","usage":{"input_tokens":20,"output_tokens":6}}"#;
+        assert_eq!(AnthropicProvider.parse_response(anthropic).unwrap(), "Sales-I US was down.");
+        assert!(AnthropicProvider.parse_response(r#"{"content":[]}"#).is_err());
+
+        let error = OpenAiProvider.parse_response("<html>Bad Gateway</html>").unwrap_err();
+        assert!(error.to_string().contains("Bad Gateway"));
+    }
+
+    #[test]
+    fn lines_are_decoded_only_once_complete() {
+        let mut lines = LineBuffer::default();
+        let text = "data: {\"text\": \"Fixed – restarted\"}\n".as_bytes();
+        // Split inside the three bytes of the en dash
+        let split = text.iter().position(|&byte| byte == 0xE2).unwrap() + 1;
+
+        assert!(lines.push(&text[..split]).is_empty());
+        assert_eq!(lines.push(&text[split..]), vec!["data: {\"text\": \"Fixed – restarted\"}"]);
+        assert_eq!(lines.push(b"a\r\nb\n\nc"), vec!["a", "b", ""]);
+        assert_eq!(lines.push(b"\n"), vec!["c"]);
+    }
+
+    #[test]
+    fn parses_openai_stream_lines() {
+        let provider = OpenAiProvider;
+        let line = r#"data: {"id":"1","choices":[{"index":0,"delta":{"content":"Sales"}}]}"#;
+        assert_eq!(provider.parse_stream_line(line).unwrap(), token("Sales"));
+        let role = r#"data: {"id":"1","choices":[{"index":0,"delta":{"role":"assistant","content":""}}]}"#;
+        assert_eq!(provider.parse_stream_line(role).unwrap(), None);
+        assert_eq!(provider.parse_stream_line("data: [DONE]").unwrap(), Some(StreamEvent::Done));
+        assert_eq!(provider.parse_stream_line(": keep-alive").unwrap(), None);
+        assert!(provider.parse_stream_line("data: {not json").is_err());
+    }
+
+    #[test]
+    fn parses_ollama_stream_lines() {
+        let provider = OllamaProvider;
+        let line = r#"{"model":"llama3","message":{"role":"assistant","content":"Sales"},"done":false}"#;
+        assert_eq!(provider.parse_stream_line(line).unwrap(), token("Sales"));
+        let done = r#"{"model":"llama3","message":{"role":"assistant","content":""},"done":true}"#;
+        assert_eq!(provider.parse_stream_line(done).unwrap(), Some(StreamEvent::Done));
+        assert_eq!(provider.parse_stream_line("").unwrap(), None);
+    }
+
+    #[test]
+    fn parses_anthropic_stream_lines() {
+        let provider = AnthropicProvider;
+        let delta = r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Sales"}}"#;
+        assert_eq!(provider.parse_stream_line(delta).unwrap(), token("Sales"));
+        assert_eq!(provider.parse_stream_line("event: content_block_delta").unwrap(), None);
+        let ping = r#"data: {"type":"ping"}"#;
+        assert_eq!(provider.parse_stream_line(ping).unwrap(), None);
+        let stop = r#"data: {"type":"message_stop"}"#;
+        assert_eq!(provider.parse_stream_line(stop).unwrap(), Some(StreamEvent::Done));
+        let error = r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        assert!(provider.parse_stream_line(error).unwrap_err().to_string().contains("Overloaded"));
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::llm::{LlmConfig, LlmRequest, LlmResponse};
+
+/// A cached model response, stored as `<hash>.json` in the cache directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmCacheEntry {
+    pub provider: String,
+    pub model: String,
+    pub temperature: f32,
+    pub created_at: DateTime<Utc>,
+    pub raw_response: String,
+    pub streamed: bool,
+}
+
+pub struct LlmCache {
+    dir: PathBuf,
+    /// Skip lookups so every request goes to the model, while still storing the new responses
+    refresh: bool,
+}
+
+impl LlmCache {
+    pub fn from_env(refresh: bool) -> Result<Self, Box<dyn Error>> {
+        let dir = env::var("LLM_CACHE_DIR").unwrap_or_else(|_| ".llm-cache".to_string());
+        fs::create_dir_all(&dir)?;
+
+        Ok(LlmCache {
+            dir: PathBuf::from(dir),
+            refresh,
+        })
+    }
+
+    /// Content address of a request: provider, model, temperature and a hash of everything sent as the prompt.
+    pub fn key(config: &LlmConfig, request: &LlmRequest) -> String {
+        let mut prompt = Sha256::new();
+        prompt.update(request.system_prompt.as_bytes());
+        prompt.update([0]);
+        prompt.update(request.prompt.as_bytes());
+        prompt.update([0]);
+        if let Some(schema) = &request.json_schema {
+            prompt.update(schema.to_string().as_bytes());
+        }
+        prompt.update([0]);
+        prompt.update(request.max_tokens.to_le_bytes());
+
+        let mut key = Sha256::new();
+        key.update(config.provider.to_string().as_bytes());
+        key.update([0]);
+        key.update(config.model.as_bytes());
+        key.update([0]);
+        key.update(request.temperature.to_le_bytes());
+        key.update(prompt.finalize());
+        format!("{:x}", key.finalize())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<LlmResponse> {
+        if self.refresh {
+            return None;
+        }
+
+        let path = self.entry_path(key);
+        let contents = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<LlmCacheEntry>(&contents) {
+            Ok(entry) => {
+                debug!("Using cached LLM response {}", key);
+                Some(LlmResponse {
+                    raw: entry.raw_response,
+                    streamed: entry.streamed,
+                })
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable LLM cache entry {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn put(&self, key: &str, config: &LlmConfig, response: &LlmResponse) -> Result<(), Box<dyn Error>> {
+        let entry = LlmCacheEntry {
+            provider: config.provider.to_string(),
+            model: config.model.clone(),
+            temperature: config.temperature,
+            created_at: Utc::now(),
+            raw_response: response.raw.clone(),
+            streamed: response.streamed,
+        };
+        fs::write(self.entry_path(key), serde_json::to_string_pretty(&entry)?)?;
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use base64::{engine::general_purpose::STANDARD, Engine};
-use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
 use csv::Reader;
 use dotenv::dotenv;
 use log::{debug, info, warn};
@@ -8,8 +8,68 @@ use std::collections::HashMap;
 use std::env;
 use std::error::Error;
 use std::fs::File;
-
-#[derive(Debug, Clone, Deserialize)]
+use std::path::Path;
+
+mod actions;
+mod ai_report;
+mod charts;
+mod cli;
+mod confluence;
+mod dashboard;
+mod editor;
+mod email;
+mod history;
+mod html;
+mod internal;
+mod json_report;
+mod jira_cache;
+mod llm;
+mod llm_cache;
+mod markdown;
+mod metrics;
+mod monthly;
+mod pdf;
+mod prompts;
+mod recurrence;
+mod report;
+mod review;
+mod slack;
+mod snapshot;
+mod structured;
+mod teams;
+mod trends;
+mod verify;
+mod webhooks;
+
+use actions::{collect_actions, format_actions_section, linked_issue_keys};
+use ai_report::{assemble_ai_report, generate_llm_exchanges, refresh_summary, AiReport, NarrativeGenerator};
+use charts::write_charts;
+use cli::{Cli, Command, HistoryCommand, ReportFormat, ReviewCommand};
+use confluence::publish_to_confluence;
+use dashboard::{retain_included, run_dashboard, Dashboard, DashboardAi, DashboardOutcome};
+use email::email_report;
+use history::{HistoryStore, ReportRecord};
+use internal::InternalNotes;
+use json_report::render_json;
+use jira_cache::JiraCache;
+use llm::LlmConfig;
+use llm_cache::LlmCache;
+use html::render_html;
+use markdown::render_markdown;
+use pdf::{write_monthly_pdf, write_pdf};
+use metrics::{compute_weekly_metrics, format_metrics_summary, metric_rows, WeeklyMetrics};
+use monthly::{month_range, previous_month_range, render_monthly_report, MonthlyReport};
+use prompts::{prompt_outages, PromptOutage, PromptTemplates};
+use recurrence::{detect_recurrences, RecurrenceConfig};
+use report::{report_incidents, Report};
+use review::{edit_draft, render_diff, DraftStore, ReviewAction, ReviewState};
+use slack::post_to_slack;
+use snapshot::{LlmSnapshot, Snapshot, SNAPSHOT_VERSION};
+use teams::post_to_teams;
+use trends::{compute_trends, TrendComparison, TRAILING_WEEKS_LONG};
+use verify::GuardMode;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 struct OutageRecord {
     #[serde(rename = "Date")]
     date: String,
@@ -25,18 +85,95 @@ struct OutageRecord {
     solution: String,
     #[serde(rename = "Severity")]
     severity: String,
+    #[serde(rename = "Detected", default)]
+    detected: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct JiraIssue {
     fields: JiraFields,
+    /// The payload this issue was parsed from, kept for history and snapshots
+    #[serde(skip)]
+    raw: String,
+}
+
+impl JiraIssue {
+    fn from_payload(payload: &str) -> Result<Self, Box<dyn Error>> {
+        let mut issue: JiraIssue = serde_json::from_str(payload)?;
+        issue.raw = payload.to_string();
+        Ok(issue)
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct JiraFields {
     description: Option<String>,
+    updated: Option<String>,
+    #[serde(default)]
+    components: Vec<JiraComponent>,
+    summary: Option<String>,
+    status: Option<JiraStatus>,
+    assignee: Option<JiraUser>,
+    #[serde(default)]
+    issuelinks: Vec<JiraIssueLink>,
+    #[serde(default)]
+    subtasks: Vec<JiraLinkedIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraComponent {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct JiraStatus {
+    name: String,
+    #[serde(rename = "statusCategory")]
+    category: Option<JiraStatusCategory>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct JiraStatusCategory {
+    /// `new`, `indeterminate` or `done`
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraUser {
+    #[serde(rename = "displayName")]
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraIssueLink {
+    #[serde(rename = "type")]
+    link_type: JiraIssueLinkType,
+    #[serde(rename = "inwardIssue")]
+    inward_issue: Option<JiraLinkedIssue>,
+    #[serde(rename = "outwardIssue")]
+    outward_issue: Option<JiraLinkedIssue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraIssueLinkType {
+    /// e.g. "is caused by"
+    inward: String,
+    /// e.g. "causes"
+    outward: String,
+}
+
+/// The short form of an issue embedded in links and subtasks.
+#[derive(Debug, Deserialize)]
+struct JiraLinkedIssue {
+    key: String,
+    fields: Option<JiraLinkedFields>,
 }
 
+#[derive(Debug, Deserialize)]
+struct JiraLinkedFields {
+    summary: Option<String>,
+    status: Option<JiraStatus>,
+}
 
 fn parse_date(date_str: &str) -> Option<NaiveDate> {
     let parts: Vec<&str> = date_str.split('/').collect();
@@ -93,24 +230,30 @@ fn extract_jira_key(url: &str) -> Option<String> {
         // Check if this part matches JIRA key pattern (LETTERS-NUMBERS)
         if part.contains('-') {
             let key_parts: Vec<&str> = part.split('-').collect();
-            if key_parts.len() == 2 {
-                if key_parts[0].chars().all(|c| c.is_ascii_uppercase()) &&
-                   key_parts[1].chars().all(|c| c.is_ascii_digit()) {
-                    return Some(part.to_string());
-                }
+            if key_parts.len() == 2
+                && key_parts[0].chars().all(|c| c.is_ascii_uppercase())
+                && key_parts[1].chars().all(|c| c.is_ascii_digit())
+            {
+                return Some(part.to_string());
             }
         }
     }
     None
 }
 
-async fn fetch_jira_details(
+/// Fetches the raw JSON payload for a JIRA issue so it can be stored as-is in the history database.
+/// `fields` restricts the response to a comma-separated list of fields.
+async fn fetch_jira_payload(
     jira_key: &str,
+    fields: Option<&str>,
     email: &str,
     token: &str,
-) -> Result<JiraIssue, Box<dyn Error>> {
+) -> Result<String, Box<dyn Error>> {
     let client = reqwest::Client::new();
-    let url = format!("https://sugarcrm.atlassian.net/rest/api/2/issue/{}", jira_key);
+    let mut url = format!("https://sugarcrm.atlassian.net/rest/api/2/issue/{}", jira_key);
+    if let Some(fields) = fields {
+        url.push_str(&format!("?fields={}", fields));
+    }
 
     // Try Bearer token first
     let response = client
@@ -139,14 +282,35 @@ async fn fetch_jira_details(
         return Err(format!("Failed to fetch JIRA issue {}: {}", jira_key, response.status()).into());
     }
 
-    let issue: JiraIssue = response.json().await?;
-    Ok(issue)
+    Ok(response.text().await?)
 }
 
 fn format_outage_entry(
     record: &OutageRecord,
     start_time: Option<String>,
     end_time: Option<String>,
+    recurrence: Option<&str>,
+) -> String {
+    format!(
+        "{}\n{}{}",
+        format_outage_header(record, start_time, end_time),
+        format_recurrence_line(recurrence),
+        format_description(&record.cause, &record.solution)
+    )
+}
+
+/// The line flagging a repeat incident under its header, or nothing.
+fn format_recurrence_line(recurrence: Option<&str>) -> String {
+    match recurrence {
+        Some(recurrence) => format!("Recurring: {}\n", recurrence),
+        None => String::new(),
+    }
+}
+
+fn format_outage_header(
+    record: &OutageRecord,
+    start_time: Option<String>,
+    end_time: Option<String>,
 ) -> String {
     let date = parse_date(&record.date)
         .map(|d| d.format("%B %d").to_string())
@@ -154,7 +318,11 @@ fn format_outage_entry(
 
     let time_range = match (start_time, end_time) {
         (Some(start), Some(end)) => {
-            format!(" ({} - {} - {}min)", start, end, record.duration)
+            if record.duration.trim().is_empty() {
+                format!(" ({} - {})", start, end)
+            } else {
+                format!(" ({} - {} - {}min)", start, end, record.duration)
+            }
         }
         _ => {
             if !record.duration.is_empty() && record.duration != "0" {
@@ -171,14 +339,7 @@ fn format_outage_entry(
         String::new()
     };
 
-    format!(
-        "{}{} {}{}\n{}",
-        date,
-        time_range,
-        record.service,
-        severity,
-        format_description(&record.cause, &record.solution)
-    )
+    format!("{}{} {}{}", date, time_range, record.service, severity)
 }
 
 fn format_description(cause: &str, solution: &str) -> String {
@@ -193,7 +354,7 @@ fn format_description(cause: &str, solution: &str) -> String {
 
     if !solution.is_empty() {
         if !description.is_empty() {
-            description.push_str(" ");
+            description.push(' ');
         }
         description.push_str(solution);
         if !solution.ends_with('.') {
@@ -204,36 +365,6 @@ fn format_description(cause: &str, solution: &str) -> String {
     description
 }
 
-#[derive(Debug, Serialize)]
-struct LMStudioRequest {
-    model: String,
-    messages: Vec<LMStudioMessage>,
-    temperature: f32,
-    max_tokens: i32,
-    stream: bool,
-}
-
-#[derive(Debug, Serialize)]
-struct LMStudioMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct LMStudioResponse {
-    choices: Vec<LMStudioChoice>,
-}
-
-#[derive(Debug, Deserialize)]
-struct LMStudioChoice {
-    message: LMStudioMessageResponse,
-}
-
-#[derive(Debug, Deserialize)]
-struct LMStudioMessageResponse {
-    content: String,
-}
-
 fn extract_time_from_description(description: &str) -> (Option<String>, Option<String>) {
     // Try to extract time range from description like "18:40 - 18:43"
     if description.contains('-') && description.contains(':') {
@@ -251,9 +382,8 @@ fn extract_time_from_description(description: &str) -> (Option<String>, Option<S
 
 fn calculate_incident_times(date: &NaiveDate, duration_str: &str, jira_description: &str) -> (String, String) {
     // First try to extract times from JIRA description
-    let (jira_start, jira_end) = extract_time_from_description(jira_description);
-    if jira_start.is_some() && jira_end.is_some() {
-        return (jira_start.unwrap(), jira_end.unwrap());
+    if let (Some(jira_start), Some(jira_end)) = extract_time_from_description(jira_description) {
+        return (jira_start, jira_end);
     }
 
     // Parse duration to get minutes
@@ -314,60 +444,76 @@ fn parse_duration_to_minutes(duration_str: &str) -> i32 {
     5 // Default to 5 minutes if parsing fails
 }
 
-fn extract_rca_and_preventative_measures(description: &str) -> String {
-    debug!("Attempting to extract RCA and Preventative Measures from description (length: {} chars)", description.len());
-    let mut extracted_content = Vec::new();
+/// The RCA and Preventative Measures sections of a JIRA description.
+#[derive(Debug, Clone, Default)]
+struct RcaSections {
+    rca: Option<String>,
+    preventative_measures: Option<String>,
+    /// A line mentioning the root cause, used when neither section is present
+    related_line: Option<String>,
+}
 
-    // Look for RCA section
-    if let Ok(rca_pattern) = regex::Regex::new(r"(?i)(?:^|\n)\s*(?:RCA|Root Cause Analysis?)\s*:?\s*\n?(.*?)(?=\n\s*(?:[A-Z][^:\n]*:|$))") {
-        if let Some(rca_match) = rca_pattern.captures(description) {
-            if let Some(rca_content) = rca_match.get(1) {
-                let cleaned_rca = rca_content.as_str().trim();
-                if !cleaned_rca.is_empty() {
-                    debug!("Found RCA section: {}", cleaned_rca);
-                    extracted_content.push(format!("RCA: {}", cleaned_rca));
-                } else {
-                    debug!("Found RCA section but content was empty after trimming");
-                }
-            }
-        } else {
-            debug!("RCA pattern did not match");
-        }
+fn extract_section(description: &str, pattern: &str, name: &str) -> Option<String> {
+    let pattern = regex::Regex::new(pattern).ok()?;
+    let Some(captures) = pattern.captures(description) else {
+        debug!("{} pattern did not match", name);
+        return None;
+    };
+    let content = captures.get(1)?.as_str().trim();
+    if content.is_empty() {
+        debug!("Found {} section but content was empty after trimming", name);
+        return None;
     }
+    debug!("Found {} section: {}", name, content);
+    Some(content.to_string())
+}
 
-    // Look for Preventative Measures section
-    if let Ok(pm_pattern) = regex::Regex::new(r"(?i)(?:^|\n)\s*(?:Preventative Measures?|Prevention|Preventive Measures?)\s*:?\s*\n?(.*?)(?=\n\s*(?:[A-Z][^:\n]*:|$))") {
-        if let Some(pm_match) = pm_pattern.captures(description) {
-            if let Some(pm_content) = pm_match.get(1) {
-                let cleaned_pm = pm_content.as_str().trim();
-                if !cleaned_pm.is_empty() {
-                    debug!("Found Preventative Measures section: {}", cleaned_pm);
-                    extracted_content.push(format!("Preventative Measures: {}", cleaned_pm));
-                } else {
-                    debug!("Found Preventative Measures section but content was empty after trimming");
-                }
-            }
-        } else {
-            debug!("Preventative Measures pattern did not match");
-        }
-    }
+fn extract_rca_sections(description: &str) -> RcaSections {
+    debug!("Attempting to extract RCA and Preventative Measures from description (length: {} chars)", description.len());
+    // Section names match in any case, but a section only ends at a "Heading:" line that starts with a capital
+    let rca = extract_section(
+        description,
+        r"(?is)(?:^|\n)\s*(?:RCA|Root Cause Analysis?)\s*:?\s*\n?(.*?)(?:\n\s*(?-i:[A-Z])[^:\n]*:|$)",
+        "RCA",
+    );
+    let preventative_measures = extract_section(
+        description,
+        r"(?is)(?:^|\n)\s*(?:Preventative Measures?|Prevention|Preventive Measures?)\s*:?\s*\n?(.*?)(?:\n\s*(?-i:[A-Z])[^:\n]*:|$)",
+        "Preventative Measures",
+    );
 
     // If no specific sections found, look for any content that might be RCA-related
-    if extracted_content.is_empty() {
+    let mut related_line = None;
+    if rca.is_none() && preventative_measures.is_none() {
         debug!("No RCA or Preventative Measures sections found, looking for fallback keywords");
-        // Look for lines that might contain root cause information
-        for line in description.lines() {
-            let line = line.trim();
-            if line.to_lowercase().contains("root cause") ||
-               line.to_lowercase().contains("caused by") ||
-               line.to_lowercase().contains("due to") {
-                debug!("Found fallback RCA line: {}", line);
-                extracted_content.push(line.to_string());
-                break; // Just take the first relevant line to keep it concise
-            }
+        // Just take the first relevant line to keep it concise
+        related_line = description.lines().map(str::trim).find(|line| {
+            let line = line.to_lowercase();
+            line.contains("root cause") || line.contains("caused by") || line.contains("due to")
+        });
+        if let Some(line) = related_line {
+            debug!("Found fallback RCA line: {}", line);
         }
     }
 
+    RcaSections {
+        rca,
+        preventative_measures,
+        related_line: related_line.map(str::to_string),
+    }
+}
+
+fn extract_rca_and_preventative_measures(description: &str) -> String {
+    let sections = extract_rca_sections(description);
+    let mut extracted_content = Vec::new();
+    if let Some(rca) = sections.rca {
+        extracted_content.push(format!("RCA: {}", rca));
+    }
+    if let Some(preventative_measures) = sections.preventative_measures {
+        extracted_content.push(format!("Preventative Measures: {}", preventative_measures));
+    }
+    extracted_content.extend(sections.related_line);
+
     let result = extracted_content.join("\n");
     if result.is_empty() {
         debug!("No RCA or Preventative Measures content extracted");
@@ -378,140 +524,215 @@ fn extract_rca_and_preventative_measures(description: &str) -> String {
     result
 }
 
-async fn call_lm_studio(
-    outages: &[OutageRecord],
-    jira_details: &HashMap<String, JiraIssue>,
-    week_number: u32,
-    week_start: &NaiveDate,
-    week_end: &NaiveDate,
-    lm_studio_url: &str,
-    model: &str,
-) -> Result<String, Box<dyn Error>> {
-    let mut outage_summaries = Vec::new();
+fn load_outages(path: &str) -> Result<Vec<OutageRecord>, Box<dyn Error>> {
+    let file = File::open(path)?;
+    let mut reader = Reader::from_reader(file);
 
+    let mut outages: Vec<OutageRecord> = Vec::new();
+    for result in reader.deserialize() {
+        match result {
+            Ok(record) => outages.push(record),
+            Err(e) => warn!("Skipping invalid record: {}", e),
+        }
+    }
+
+    Ok(outages)
+}
+
+fn outages_in_range(outages: &[OutageRecord], start: &NaiveDate, end: &NaiveDate) -> Vec<OutageRecord> {
+    let mut filtered: Vec<OutageRecord> = outages
+        .iter()
+        .filter(|record| {
+            parse_date(&record.date)
+                .map(|date| date >= *start && date <= *end)
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect();
+
+    filtered.sort_by(|a, b| {
+        let date_a = parse_date(&a.date);
+        let date_b = parse_date(&b.date);
+        date_a.cmp(&date_b)
+    });
+
+    filtered
+}
+
+/// Fetches JIRA details for every outage not already in `jira_details`, going through the on-disk cache.
+/// Returns true if any ticket could not be fetched.
+async fn fetch_jira_for_outages(
+    outages: &[OutageRecord],
+    jira_details: &mut HashMap<String, JiraIssue>,
+    cache: &JiraCache,
+    history: Option<&HistoryStore>,
+    email: &str,
+    token: &str,
+) -> bool {
+    let mut jira_fetch_failed = false;
     for record in outages {
-        let jira_desc = if let Some(jira_key) = extract_jira_key(&record.ticket) {
-            debug!("Processing JIRA ticket: {}", jira_key);
-            if let Some(issue) = jira_details.get(&jira_key) {
-                if let Some(desc) = issue.fields.description.as_ref() {
-                    debug!("JIRA description found for {}, extracting RCA/PM", jira_key);
-                    extract_rca_and_preventative_measures(desc)
-                } else {
-                    debug!("No description field found for {}", jira_key);
-                    String::new()
+        if let Some(jira_key) = extract_jira_key(&record.ticket) {
+            debug!("Found JIRA key: {}", jira_key);
+            if !jira_details.contains_key(&jira_key) {
+                match load_jira_issue(&jira_key, cache, history, email, token).await {
+                    Ok(issue) => {
+                        jira_details.insert(jira_key.clone(), issue);
+                    }
+                    Err(e) => {
+                        warn!("Failed to fetch JIRA details for {}: {}", jira_key, e);
+                        jira_fetch_failed = true;
+                    }
                 }
             } else {
-                debug!("JIRA details not found in cache for {}", jira_key);
-                String::new()
+                debug!("JIRA details already loaded for {}", jira_key);
             }
         } else {
-            debug!("Could not extract JIRA key from ticket: {}", record.ticket);
-            String::new()
-        };
-
-        // Calculate start and end times
-        let incident_date = parse_date(&record.date).unwrap_or_else(|| Local::now().date_naive());
-        let full_jira_desc = if let Some(jira_key) = extract_jira_key(&record.ticket) {
-            jira_details.get(&jira_key)
-                .and_then(|issue| issue.fields.description.as_ref())
-                .map(|d| d.clone())
-                .unwrap_or_default()
-        } else {
-            String::new()
-        };
-        let (start_time, end_time) = calculate_incident_times(&incident_date, &record.duration, &full_jira_desc);
-
-        let summary = format!(
-            "Date: {}\nService: {}\nStart Time: {} UTC\nEnd Time: {} UTC\nDuration: {} minutes\nSeverity: {}\nCause: {}\nSolution: {}\nJIRA RCA/Preventative Measures: {}\n",
-            record.date, record.service, start_time, end_time, record.duration, record.severity,
-            record.cause, record.solution,
-            if jira_desc.is_empty() { "N/A".to_string() } else { jira_desc }
-        );
-        outage_summaries.push(summary);
+            debug!("No JIRA key found in ticket URL: {}", record.ticket);
+        }
     }
+    jira_fetch_failed
+}
 
-    let prompt = format!(
-        r#"Create a concise weekly stability report for week {} ({} to {}).
-
-Format EXACTLY like these examples:
-
-Sept 15th (18:40 - 18:43 - 3min) Sales-I DE API (Regional)
-A configuration change by Microsoft Azure caused a temporary disruption to the CDN. As this originated from Azure's platform team, it was outside of our control. No action is required on our side, and service has since stabilized.
-
-Sept 17th (15:07 - 15:12 - 5min) Sugar Market Mail App API (Regional)
-The IIS logs initially pointed to a configuration issue, but further review with the Market team confirmed the root cause is a bug in the MsgApp application, which surfaced as database connectivity symptoms. Because MsgApp's current logging is insufficient to isolate the cause, Rachel from Market has opened a ticket to add more actionable logging. In parallel, the Market team is already migrating functionality from MsgApp to Vulcan with the goal of fully retiring MsgApp once the transition is complete.
+/// Fetches the issues linked from the given outages' JIRA issues, so their status and assignee are current.
+/// Failures only lose the assignee, since the link itself carries the summary and status.
+async fn fetch_linked_issues(
+    outages: &[OutageRecord],
+    jira_details: &mut HashMap<String, JiraIssue>,
+    cache: &JiraCache,
+    history: Option<&HistoryStore>,
+    email: &str,
+    token: &str,
+) {
+    for key in linked_issue_keys(outages, jira_details) {
+        if jira_details.contains_key(&key) {
+            continue;
+        }
+        match load_jira_issue(&key, cache, history, email, token).await {
+            Ok(issue) => {
+                jira_details.insert(key, issue);
+            }
+            Err(e) => warn!("Failed to fetch linked JIRA issue {}, using the status from the link: {}", key, e),
+        }
+    }
+}
 
-Raw data:
-{}
+async fn load_jira_issue(
+    jira_key: &str,
+    cache: &JiraCache,
+    history: Option<&HistoryStore>,
+    email: &str,
+    token: &str,
+) -> Result<JiraIssue, Box<dyn Error>> {
+    let cached = cache.get(jira_key);
 
-CRITICAL REQUIREMENTS:
-- Each incident MUST clearly explain what we're doing to PREVENT it from happening again
-- If preventative measures aren't clear from the data, mention what should be done in the AI Recommendations section
-- Keep descriptions to 2-3 sentences maximum
-- Use the provided Start Time and End Time in UTC format (HH:MM - HH:MM)
-- Use Month day format (Sept 15th, not September 15)
-- Format: Sept 15th (18:40 - 18:43 - 3min) Service Name (Severity)
-- Combine root cause, immediate resolution, AND prevention steps
-- Include severity in parentheses if available
-- End the email portion with "Regards,"
-- This will be read by the CEO and CTO
+    if let Some(entry) = &cached {
+        if cache.offline || cache.is_fresh(entry) {
+            debug!("Using cached JIRA details for {}", jira_key);
+            cache.record(entry);
+            return JiraIssue::from_payload(&entry.payload);
+        }
 
-AFTER the email content, add a separate section titled "--- AI RECOMMENDATIONS ---" with any additional prevention suggestions you think would be beneficial that weren't mentioned in the incidents.
-"#,
-        week_number,
-        week_start.format("%B %d"),
-        week_end.format("%B %d"),
-        outage_summaries.join("\n---\n")
-    );
+        // Stale entry, only refetch the full issue if it changed since we cached it
+        debug!("Checking whether cached JIRA details for {} are still current", jira_key);
+        let updated = fetch_jira_payload(jira_key, Some("updated"), email, token)
+            .await
+            .and_then(|payload| Ok(serde_json::from_str::<JiraIssue>(&payload)?.fields.updated));
+        match updated {
+            Ok(updated) if updated.is_some() && updated == entry.updated => {
+                debug!("Cached JIRA details for {} are unchanged", jira_key);
+                cache.touch(entry)?;
+                cache.record(entry);
+                return JiraIssue::from_payload(&entry.payload);
+            }
+            Ok(_) => debug!("JIRA issue {} changed since it was cached", jira_key),
+            Err(e) => warn!("Could not check whether {} changed: {}", jira_key, e),
+        }
+    } else if cache.offline {
+        return Err(format!("{} is not in the JIRA cache (offline mode)", jira_key).into());
+    }
 
-    let request = LMStudioRequest {
-        model: model.to_string(),
-        messages: vec![
-            LMStudioMessage {
-                role: "system".to_string(),
-                content: "You are a technical writer creating executive stability reports. Focus heavily on PREVENTION - each incident must clearly explain what we're doing to prevent recurrence. Be concise and direct. Include AI recommendations after the email.".to_string(),
-            },
-            LMStudioMessage {
-                role: "user".to_string(),
-                content: prompt.clone(),
-            },
-        ],
-        temperature: 0.3,
-        max_tokens: 4000,
-        stream: false,
+    debug!("Fetching JIRA details for {}", jira_key);
+    let payload = match fetch_jira_payload(jira_key, None, email, token).await {
+        Ok(payload) => payload,
+        Err(e) => {
+            if let Some(entry) = cached {
+                warn!("Using stale cached JIRA details for {}: {}", jira_key, e);
+                return JiraIssue::from_payload(&entry.payload);
+            }
+            return Err(e);
+        }
     };
+    let issue = JiraIssue::from_payload(&payload)?;
+    debug!("Successfully fetched JIRA details for {}", jira_key);
+
+    let entry = cache.put(jira_key, issue.fields.updated.clone(), &payload)?;
+    cache.record(&entry);
+    if let Some(history) = history {
+        if let Err(e) = history.save_jira_payload(jira_key, &payload) {
+            warn!("Failed to store JIRA payload for {}: {}", jira_key, e);
+        }
+    }
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(300))
-        .build()?;
+    Ok(issue)
+}
 
-    debug!("Sending request to LM Studio");
-    debug!("LM Studio request prompt:\n{}", prompt);
+fn render_report(report: &Report) -> String {
+    let mut summary = format_metrics_summary(&report.metrics);
+    if let Some(actions) = format_actions_section(&report.actions) {
+        summary.push_str(&format!("\n\n{}", actions));
+    }
 
-    let response = client
-        .post(lm_studio_url)
-        .json(&request)
-        .send()
-        .await?;
+    let mut text = String::new();
 
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("LM Studio API error {}: {}", status, error_text).into());
+    if let Some(ai_report) = report.ai {
+        text.push_str(&format!("{}\n", "=".repeat(80)));
+        text.push_str("WEEKLY STABILITY REPORT (AI-Generated)\n");
+        text.push_str(&format!("{}\n\n", "=".repeat(80)));
+        text.push_str(&format!("{}\n\n", summary));
+        text.push_str(&ai_report.text);
+        return text;
     }
 
-    debug!("Parsing LM Studio response");
-    let response_text = response.text().await?;
+    // Fallback to original formatting
+    text.push_str(&format!("{}\n", "=".repeat(80)));
+    text.push_str("WEEKLY STABILITY REPORT\n");
+    text.push_str(&format!(
+        "Week {} ({} - {})\n",
+        report.week_number,
+        report.week_start.format("%B %d"),
+        report.week_end.format("%B %d")
+    ));
+    text.push_str("All times UTC\n");
+    text.push_str(&format!("{}\n\n", "=".repeat(80)));
+    text.push_str(&format!("{}\n\n", summary));
+
+    for entry in report.standard_entries() {
+        text.push_str(&format!("{}\n\n", entry));
+    }
 
-    // Try to parse the JSON response
-    let lm_response: LMStudioResponse = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse LM Studio response: {}. Response: {}", e, &response_text[..response_text.len().min(500)]))?;
+    text.push_str("Regards,");
+    text
+}
 
-    if let Some(choice) = lm_response.choices.first() {
-        Ok(choice.message.content.clone())
-    } else {
-        Err("No response from LM Studio".into())
-    }
+/// Renders the report in the requested format, falling back to text if that fails.
+/// Text is what gets stored in history and snapshots.
+fn render_output(report: &Report, format: ReportFormat) -> String {
+    let rendered = match format {
+        ReportFormat::Text => Ok(render_report(report)),
+        ReportFormat::Markdown => {
+            let charts = write_charts(report).unwrap_or_else(|e| {
+                warn!("Could not write the charts, leaving them out of the Markdown: {}", e);
+                Vec::new()
+            });
+            Ok(render_markdown(report, &charts))
+        }
+        ReportFormat::Html => render_html(report),
+        ReportFormat::Json => render_json(report).map_err(Into::into),
+    };
+    rendered.unwrap_or_else(|e| {
+        warn!("Could not render the {:?} report, using text instead: {}", format, e);
+        render_report(report)
+    })
 }
 
 fn get_week_number(date: &NaiveDate) -> u32 {
@@ -522,21 +743,23 @@ fn get_week_number(date: &NaiveDate) -> u32 {
 async fn main() -> Result<(), Box<dyn Error>> {
     dotenv().ok();
     env_logger::init();
+    let cli = Cli::parse_args();
 
-    let jira_token = env::var("JIRA_TOKEN")
-        .expect("JIRA_TOKEN not found in environment variables");
-
-    let jira_email = env::var("JIRA_EMAIL")
-        .unwrap_or_else(|_| {
-            info!("JIRA_EMAIL not set, using default");
-            "automation@sugarcrm.com".to_string()
-        });
+    match &cli.command {
+        Some(Command::Regenerate { from_snapshot }) => {
+            return regenerate_from_snapshot(from_snapshot, cli.format, cli.pdf.as_deref());
+        }
+        Some(Command::Review { week, action }) => return run_review(&cli, week.as_deref(), action).await,
+        Some(Command::Monthly { month }) => return run_monthly(&cli, month.as_deref()).await,
+        Some(Command::History { action }) => return run_history(action),
+        None => {}
+    }
 
-    let lm_studio_url = env::var("LM_STUDIO_URL")
-        .unwrap_or_else(|_| "http://localhost:1234/v1/chat/completions".to_string());
+    // Offline runs only read the cache, so a token isn't needed
+    let (jira_email, jira_token) = jira_credentials(!cli.offline)?;
 
-    let lm_studio_model = env::var("LM_STUDIO_MODEL")
-        .unwrap_or_else(|_| "local-model".to_string());
+    let llm_config = LlmConfig::from_env()?;
+    let guard_mode = GuardMode::from_env()?;
 
     let (week_start, week_end) = get_previous_week_range();
     let week_number = get_week_number(&week_start);
@@ -546,130 +769,693 @@ async fn main() -> Result<(), Box<dyn Error>> {
              week_start.format("%B %d"),
              week_end.format("%B %d"));
 
-    let file = File::open("outages.csv")?;
-    let mut reader = Reader::from_reader(file);
+    let all_outages = load_outages("outages.csv")?;
+    let outages = outages_in_range(&all_outages, &week_start, &week_end);
+    let history_start = week_start - Duration::weeks(TRAILING_WEEKS_LONG as i64);
+    let history_outages = outages_in_range(&all_outages, &history_start, &(week_start - Duration::days(1)));
 
-    let mut outages: Vec<OutageRecord> = Vec::new();
     let mut jira_details: HashMap<String, JiraIssue> = HashMap::new();
 
-    for result in reader.deserialize() {
-        let record: OutageRecord = match result {
-            Ok(r) => r,
-            Err(e) => {
-                warn!("Skipping invalid record: {}", e);
-                continue;
-            }
-        };
+    let history_path = history_path();
+    let history = match HistoryStore::open(&history_path) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            warn!("Could not open history database {}: {}", history_path, e);
+            None
+        }
+    };
 
-        if let Some(date) = parse_date(&record.date) {
-            if date >= week_start && date <= week_end {
-                outages.push(record);
-            }
+    if let Some(history) = &history {
+        if let Err(e) = history.upsert_incidents(&all_outages) {
+            warn!("Failed to store incidents in history database: {}", e);
         }
     }
 
-    outages.sort_by(|a, b| {
-        let date_a = parse_date(&a.date);
-        let date_b = parse_date(&b.date);
-        date_a.cmp(&date_b)
-    });
+    let jira_cache = JiraCache::from_env(cli.offline, cli.record_fixtures.clone())?;
 
     info!("Found {} outage(s)", outages.len());
     debug!("Fetching JIRA details...");
 
-    let mut jira_fetch_failed = false;
-    for record in &outages {
-        if let Some(jira_key) = extract_jira_key(&record.ticket) {
-            debug!("Found JIRA key: {}", jira_key);
-            if !jira_details.contains_key(&jira_key) {
-                debug!("Fetching JIRA details for {}", jira_key);
-                match fetch_jira_details(&jira_key, &jira_email, &jira_token).await {
-                    Ok(issue) => {
-                        debug!("Successfully fetched JIRA details for {}", jira_key);
-                        jira_details.insert(jira_key.clone(), issue);
-                    }
-                    Err(e) => {
-                        warn!("Failed to fetch JIRA details for {}: {}", jira_key, e);
-                        jira_fetch_failed = true;
-                    }
-                }
-            } else {
-                debug!("JIRA details already cached for {}", jira_key);
-            }
-        } else {
-            debug!("No JIRA key found in ticket URL: {}", record.ticket);
-        }
+    let mut jira_fetch_failed =
+        fetch_jira_for_outages(&outages, &mut jira_details, &jira_cache, history.as_ref(), &jira_email, &jira_token).await;
+
+    debug!("Fetching JIRA details for {} historical outage(s)", history_outages.len());
+    if fetch_jira_for_outages(&history_outages, &mut jira_details, &jira_cache, history.as_ref(), &jira_email, &jira_token).await {
+        jira_fetch_failed = true;
     }
 
     if jira_fetch_failed {
         warn!("Some JIRA tickets could not be fetched, using CSV data only");
     }
 
-    // Try to use LM Studio to format the report if configured
+    let linked_outages: Vec<OutageRecord> = history_outages.iter().chain(&outages).cloned().collect();
+    fetch_linked_issues(&linked_outages, &mut jira_details, &jira_cache, history.as_ref(), &jira_email, &jira_token).await;
+
+    // Try to use the configured LLM provider to format the report
     let use_ai = env::var("USE_AI").unwrap_or_else(|_| "true".to_string()) == "true";
 
     if use_ai {
         info!("Generating AI-formatted report...");
     }
 
-    let ai_result = if use_ai {
-        call_lm_studio(
-            &outages,
-            &jira_details,
-            week_number,
-            &week_start,
-            &week_end,
-            &lm_studio_url,
-            &lm_studio_model,
-        )
-        .await
+    let data_since = all_outages.iter().filter_map(|r| parse_date(&r.date)).min();
+    let trends = compute_trends(&history_outages, &jira_details, &week_start, data_since);
+    let recurrence_config = RecurrenceConfig::from_env();
+    let recurrences = detect_recurrences(&outages, &history_outages, &jira_details, &recurrence_config);
+    let mut prompt_outages = prompt_outages(&outages, &jira_details, &recurrences);
+    let mut outages = outages;
+
+    let ai_setup = if use_ai {
+        Some((PromptTemplates::load()?, LlmCache::from_env(cli.refresh_ai)?))
     } else {
-        Err("AI generation disabled".into())
+        None
     };
+    let mut llm_snapshot = match &ai_setup {
+        Some((templates, llm_cache)) => {
+            let mut llm = generate_llm_exchanges(
+                &llm_config,
+                llm_cache,
+                templates,
+                &prompt_outages,
+                week_number,
+                &week_start,
+                &week_end,
+            )
+            .await?;
+            llm.guard = guard_mode.to_string();
+            Some(llm)
+        }
+        None => None,
+    };
+
+    if cli.dashboard {
+        let mut dashboard = Dashboard {
+            week_number,
+            week_start,
+            week_end,
+            outages: &outages,
+            prompt_outages: &prompt_outages,
+            jira_details: &jira_details,
+            ai: llm_snapshot.as_mut().zip(ai_setup.as_ref()).map(|(llm, (templates, cache))| DashboardAi {
+                llm,
+                generator: NarrativeGenerator { config: &llm_config, cache, templates },
+            }),
+        };
+        match run_dashboard(&mut dashboard).await? {
+            DashboardOutcome::Quit => {
+                info!("Closed the dashboard without writing the report");
+                return Ok(());
+            }
+            DashboardOutcome::Export { included, edited } => {
+                let excluded: Vec<String> = outages
+                    .iter()
+                    .zip(&included)
+                    .filter(|(_, included)| !**included)
+                    .map(|(record, _)| format!("{} {}", record.date.trim(), record.service.trim()))
+                    .collect();
+                if !excluded.is_empty() {
+                    info!("Excluded in the dashboard: {}", excluded.join(", "));
+                }
+                keep_included(&mut outages, &mut prompt_outages, llm_snapshot.as_mut(), &included);
+                if let Some((llm, (templates, cache))) = llm_snapshot.as_mut().zip(ai_setup.as_ref()) {
+                    if edited || !excluded.is_empty() {
+                        info!("Requesting a new summary for the reviewed incidents...");
+                        let generator = NarrativeGenerator { config: &llm_config, cache, templates };
+                        if let Err(e) =
+                            refresh_summary(&generator, llm, &prompt_outages, week_number, &week_start, &week_end).await
+                        {
+                            warn!("Could not request a new summary, leaving it out: {}", e);
+                            llm.summary = None;
+                        }
+                    }
+                }
+            }
+        }
+    }
 
-    match ai_result {
-        Ok(ai_report) => {
-            println!("{}", "=".repeat(80));
-            println!("WEEKLY STABILITY REPORT (AI-Generated)");
-            println!("{}", "=".repeat(80));
-            println!();
-            println!("{}", ai_report);
+    let metrics = compute_weekly_metrics(&outages, &jira_details);
+    let ai_result = match llm_snapshot.as_mut() {
+        Some(llm) => {
+            assemble_ai_report(llm, &outages, &prompt_outages, &jira_details, week_number, &week_start, &week_end).map(
+                |(ai_report, findings)| {
+                    llm.findings = findings;
+                    ai_report
+                },
+            )
         }
+        None => Err("AI generation disabled".into()),
+    };
+
+    let ai_generated = ai_result.is_ok();
+    let ai_report = match ai_result {
+        Ok(ai_report) => Some(ai_report),
         Err(e) => {
             warn!("Could not generate AI report: {}", e);
             info!("Using standard format");
+            None
+        }
+    };
+    let report_data = Report {
+        week_number,
+        week_start,
+        week_end,
+        metrics: metric_rows(&metrics, Some(&trends)),
+        kpis: &metrics,
+        trends: &trends,
+        actions: collect_actions(&outages, &history_outages, &jira_details),
+        incidents: report_incidents(&outages, &prompt_outages, &jira_details, ai_report.as_ref()),
+        ai: ai_report.as_ref(),
+    };
+    let report = render_report(&report_data);
 
-            // Fallback to original formatting
-            println!("{}", "=".repeat(80));
-            println!("WEEKLY STABILITY REPORT");
-            println!("Week {} ({} - {})", week_number, week_start.format("%B %d"), week_end.format("%B %d"));
-            println!("All times UTC");
-            println!("{}", "=".repeat(80));
-            println!();
-
-            for record in &outages {
-                let jira_key = extract_jira_key(&record.ticket);
-                let (start_time, end_time) = if let Some(ref key) = jira_key {
-                    if let Some(issue) = jira_details.get(key) {
-                        if let Some(ref desc) = issue.fields.description {
-                            extract_time_from_description(desc)
-                        } else {
-                            (None, None)
-                        }
-                    } else {
-                        (None, None)
-                    }
-                } else {
-                    (None, None)
+    println!("{}", render_output(&report_data, cli.format));
+
+    // Recommendations and check findings are for the team, never the executive email
+    if let Some(llm) = &llm_snapshot {
+        let notes = InternalNotes {
+            week_number,
+            week_start,
+            week_end,
+            recommendations: ai_report.as_ref().map(|ai_report| ai_report.recommendations.as_slice()).unwrap_or_default(),
+            findings: &llm.findings,
+        };
+        match notes.save() {
+            Ok(path) => info!("Saved internal notes to {}", path.display()),
+            Err(e) => warn!("Failed to save internal notes: {}", e),
+        }
+    }
+
+    let snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        created_at: Utc::now(),
+        week_number,
+        week_start,
+        week_end,
+        data_since,
+        outages: outages.clone(),
+        history_outages: history_outages.clone(),
+        recurrence: Some(recurrence_config),
+        jira_payloads: jira_details
+            .iter()
+            .map(|(key, issue)| (key.clone(), issue.raw.clone()))
+            .collect(),
+        llm: llm_snapshot,
+        output: report.clone(),
+    };
+    match snapshot.save() {
+        Ok(path) => info!("Saved report snapshot to {}", path.display()),
+        Err(e) => warn!("Failed to save report snapshot: {}", e),
+    }
+
+    if let Some(history) = &history {
+        let saved = history.save_report(&ReportRecord {
+            week_number,
+            week_start,
+            week_end,
+            ai_generated,
+            model: if ai_generated { Some(llm_config.model.clone()) } else { None },
+            outages: &outages,
+            jira_keys: outages.iter().filter_map(|r| extract_jira_key(&r.ticket)).collect(),
+            output: &report,
+        });
+        match saved {
+            Ok(id) => info!("Saved report {} to history database", id),
+            Err(e) => warn!("Failed to save report to history database: {}", e),
+        }
+    }
+
+    let drafts = DraftStore::for_week(&week_start, week_number);
+    match drafts.save_draft(&snapshot, ReviewAction::Generated) {
+        Ok(Some(revision)) => info!("Saved the report as draft {} of week {}", revision, drafts.week()),
+        Ok(None) => info!("The report is the same as the latest draft of week {}", drafts.week()),
+        // Without the draft, its approval can't be checked
+        Err(e) if !requested_destinations(&cli).is_empty() => {
+            return Err(format!("Could not save the report draft: {}", e).into());
+        }
+        Err(e) => warn!("Failed to save the report draft: {}", e),
+    }
+
+    // Written after the report is saved, so a bad path doesn't lose it
+    if let Some(path) = &cli.pdf {
+        write_pdf(&report_data, path)?;
+    }
+
+    deliver_draft(&cli, &drafts, &report_data, &jira_email, &jira_token).await
+}
+
+/// Prints the reliability report for a calendar month, and writes it as a PDF with `--pdf`.
+async fn run_monthly(cli: &Cli, month: Option<&str>) -> Result<(), Box<dyn Error>> {
+    if !requested_destinations(cli).is_empty() {
+        return Err("Monthly reports can't be delivered, write them with --pdf instead".into());
+    }
+    if cli.format != ReportFormat::Text {
+        return Err("Monthly reports are printed as text, use --pdf for a PDF".into());
+    }
+
+    let (month_start, month_end) = match month {
+        Some(month) => month
+            .split_once('-')
+            .and_then(|(year, month)| month_range(year.parse().ok()?, month.parse().ok()?))
+            .ok_or_else(|| format!("Invalid month {}", month))?,
+        None => previous_month_range(),
+    };
+    info!("Generating the monthly report for {}", month_start.format("%B %Y"));
+
+    // The previous month's incidents are needed for the comparison
+    let previous_start = (month_start - Duration::days(1)).with_day(1).expect("the first is a valid day");
+    let all_outages = load_outages("outages.csv")?;
+    let outages = outages_in_range(&all_outages, &previous_start, &month_end);
+
+    let (jira_email, jira_token) = jira_credentials(!cli.offline)?;
+    let jira_cache = JiraCache::from_env(cli.offline, cli.record_fixtures.clone())?;
+    let history = match HistoryStore::open(&history_path()) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            warn!("Could not open history database {}: {}", history_path(), e);
+            None
+        }
+    };
+    let mut jira_details = HashMap::new();
+    if fetch_jira_for_outages(&outages, &mut jira_details, &jira_cache, history.as_ref(), &jira_email, &jira_token).await {
+        warn!("Some JIRA tickets could not be fetched, using CSV data only");
+    }
+
+    let report = MonthlyReport::build(&all_outages, &jira_details, month_start, month_end);
+    println!("{}", render_monthly_report(&report));
+    if let Some(path) = &cli.pdf {
+        write_monthly_pdf(&report, path)?;
+    }
+    Ok(())
+}
+
+fn history_path() -> String {
+    env::var("HISTORY_DB").unwrap_or_else(|_| "history.db".to_string())
+}
+
+/// Lists or prints the reports stored in the history database.
+fn run_history(action: &HistoryCommand) -> Result<(), Box<dyn Error>> {
+    let history = HistoryStore::open(&history_path())?;
+    match action {
+        HistoryCommand::List { week } => {
+            for report in history.reports()? {
+                let report_week = format!("{}-{:02}", report.week_start.format("%G"), report.week_number);
+                if week.as_ref().is_some_and(|week| *week != report_week) {
+                    continue;
+                }
+                let generated_by = match (report.ai_generated, &report.model) {
+                    (true, Some(model)) => format!("AI ({})", model),
+                    (true, None) => "AI".to_string(),
+                    (false, _) => "standard".to_string(),
                 };
+                println!("{:>5}  week {}  {}  {}", report.id, report_week, report.generated_at, generated_by);
+            }
+        }
+        HistoryCommand::Show { id } => {
+            let output = history.report_output(*id)?.ok_or_else(|| format!("No report {} in {}", id, history_path()))?;
+            println!("{}", output);
+        }
+    }
+    Ok(())
+}
 
-                let entry = format_outage_entry(&record, start_time, end_time);
-                println!("{}\n", entry);
+/// JIRA email and API token, which Confluence also signs in with. A missing token is only
+/// an error if `token_required` is set.
+fn jira_credentials(token_required: bool) -> Result<(String, String), Box<dyn Error>> {
+    let jira_token = match env::var("JIRA_TOKEN") {
+        Ok(token) => token,
+        Err(_) if token_required => return Err("JIRA_TOKEN not found in environment variables".into()),
+        Err(_) => String::new(),
+    };
+
+    let jira_email = env::var("JIRA_EMAIL")
+        .unwrap_or_else(|_| {
+            info!("JIRA_EMAIL not set, using default");
+            "automation@sugarcrm.com".to_string()
+        });
+    Ok((jira_email, jira_token))
+}
+
+/// Drops the incidents left out in the dashboard from the outages, their prompts and the LLM's
+/// responses together, so the three stay in the same order.
+fn keep_included(
+    outages: &mut Vec<OutageRecord>,
+    prompt_outages: &mut Vec<PromptOutage>,
+    llm: Option<&mut LlmSnapshot>,
+    included: &[bool],
+) {
+    *outages = retain_included(std::mem::take(outages), included);
+    *prompt_outages = retain_included(std::mem::take(prompt_outages), included);
+    if let Some(llm) = llm {
+        llm.incidents = retain_included(std::mem::take(&mut llm.incidents), included);
+    }
+}
+
+fn requested_destinations(cli: &Cli) -> Vec<&'static str> {
+    [(cli.send, "email"), (cli.slack, "Slack"), (cli.teams, "Teams"), (cli.confluence, "Confluence")]
+        .into_iter()
+        .filter(|(requested, _)| *requested)
+        .map(|(_, destination)| destination)
+        .collect()
+}
+
+/// Delivers the report if the week's latest draft, which it was saved as, has been approved and
+/// the report still renders to the reviewed text. Destinations the draft was already delivered to
+/// are skipped unless `--redeliver` is given, and those it reaches are recorded even if others fail.
+async fn deliver_draft(
+    cli: &Cli,
+    drafts: &DraftStore,
+    report: &Report<'_>,
+    jira_email: &str,
+    jira_token: &str,
+) -> Result<(), Box<dyn Error>> {
+    let requested = requested_destinations(cli);
+    if requested.is_empty() {
+        return Ok(());
+    }
+
+    let (status, draft) = drafts.latest()?;
+    if status.state != ReviewState::Approved {
+        return Err(format!(
+            "Not delivering draft {} of week {}, it is {}. Submit it with `review submit` and have it approved with `review approve` first",
+            status.revision,
+            drafts.week(),
+            status.state
+        )
+        .into());
+    }
+    // Every channel is built from `report`, so it has to be the report that was reviewed, not just the same text
+    let rebuilt = render_report(report);
+    if rebuilt != draft.output {
+        // The error is printed on one line, so the differences go to stderr before it
+        eprint!("{}", render_diff(&draft.output, &rebuilt, "approved", "rebuilt"));
+        return Err(format!(
+            "Not delivering draft {} of week {}, the report rebuilt from its CSV rows, JIRA issues, LLM responses \
+             and guard mode differs from the approved text",
+            status.revision,
+            drafts.week()
+        )
+        .into());
+    }
+
+    let (already, destinations): (Vec<&str>, Vec<&str>) =
+        requested.iter().partition(|destination| status.delivered.iter().any(|delivered| delivered == *destination));
+    let destinations = if cli.redeliver || cli.dry_run || already.is_empty() {
+        requested
+    } else if destinations.is_empty() {
+        return Err(format!(
+            "Draft {} of week {} was already delivered to {}, pass --redeliver to deliver it again",
+            status.revision,
+            drafts.week(),
+            already.join(", ")
+        )
+        .into());
+    } else {
+        warn!(
+            "Draft {} of week {} was already delivered to {}, skipping it there. Pass --redeliver to deliver it again",
+            status.revision,
+            drafts.week(),
+            already.join(", ")
+        );
+        destinations
+    };
+
+    let (delivered, failed) = deliver_report(cli, &destinations, report, &draft.output, jira_email, jira_token).await;
+    if !cli.dry_run && !delivered.is_empty() {
+        drafts.record_delivery(&delivered)?;
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Could not deliver the report to {}", failed.join(", ")).into())
+    }
+}
+
+/// Sends the report to each of `destinations`. One failing doesn't stop the others.
+/// Returns the destinations it was delivered to and, for those that failed, the destination and its error.
+async fn deliver_report(
+    cli: &Cli,
+    destinations: &[&'static str],
+    report: &Report<'_>,
+    text: &str,
+    jira_email: &str,
+    jira_token: &str,
+) -> (Vec<&'static str>, Vec<String>) {
+    let mut delivered = Vec::new();
+    let mut failed = Vec::new();
+
+    for &destination in destinations {
+        let result = match destination {
+            "email" => email_report(report, text, cli.dry_run).await,
+            "Slack" => post_to_slack(report, cli.dry_run).await,
+            "Teams" => post_to_teams(report, cli.dry_run).await,
+            "Confluence" => publish_to_confluence(report, jira_email, jira_token, cli.dry_run).await,
+            other => Err(format!("Unknown destination {}", other).into()),
+        };
+        match result {
+            Ok(()) => delivered.push(destination),
+            Err(e) => failed.push(format!("{} ({})", destination, e)),
+        }
+    }
+    (delivered, failed)
+}
+
+/// The data a report is rendered from, rebuilt from a snapshot bundle without contacting JIRA or the LLM.
+struct SnapshotReport {
+    jira_details: HashMap<String, JiraIssue>,
+    metrics: WeeklyMetrics,
+    trends: TrendComparison,
+    prompt_outages: Vec<PromptOutage>,
+    ai_report: Option<AiReport>,
+}
+
+impl SnapshotReport {
+    fn build(snapshot: &Snapshot) -> Result<Self, Box<dyn Error>> {
+        let mut jira_details = HashMap::new();
+        for (key, payload) in &snapshot.jira_payloads {
+            jira_details.insert(key.clone(), JiraIssue::from_payload(payload)?);
+        }
+
+        let metrics = compute_weekly_metrics(&snapshot.outages, &jira_details);
+        let trends = compute_trends(&snapshot.history_outages, &jira_details, &snapshot.week_start, snapshot.data_since);
+        let recurrences = detect_recurrences(
+            &snapshot.outages,
+            &snapshot.history_outages,
+            &jira_details,
+            &snapshot.recurrence.unwrap_or_else(RecurrenceConfig::from_env),
+        );
+        let prompt_outages = prompt_outages(&snapshot.outages, &jira_details, &recurrences);
+        // A failed AI report fell back to the standard format when the snapshot was taken, so do the same here
+        let ai_report = match &snapshot.llm {
+            Some(llm) if !llm.incidents.is_empty() || llm.summary.is_some() => match assemble_ai_report(
+                llm,
+                &snapshot.outages,
+                &prompt_outages,
+                &jira_details,
+                snapshot.week_number,
+                &snapshot.week_start,
+                &snapshot.week_end,
+            ) {
+                Ok((ai_report, _)) => Some(ai_report),
+                Err(e) => {
+                    warn!("Could not rebuild AI report: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        Ok(SnapshotReport { jira_details, metrics, trends, prompt_outages, ai_report })
+    }
+
+    fn report<'a>(&'a self, snapshot: &'a Snapshot) -> Report<'a> {
+        Report {
+            week_number: snapshot.week_number,
+            week_start: snapshot.week_start,
+            week_end: snapshot.week_end,
+            metrics: metric_rows(&self.metrics, Some(&self.trends)),
+            kpis: &self.metrics,
+            trends: &self.trends,
+            actions: collect_actions(&snapshot.outages, &snapshot.history_outages, &self.jira_details),
+            incidents: report_incidents(&snapshot.outages, &self.prompt_outages, &self.jira_details, self.ai_report.as_ref()),
+            ai: self.ai_report.as_ref(),
+        }
+    }
+}
+
+/// Rebuilds a report from a snapshot bundle without contacting JIRA or the LLM.
+fn regenerate_from_snapshot(path: &Path, format: ReportFormat, pdf: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let snapshot = Snapshot::load(path)?;
+    info!(
+        "Regenerating week {} ({} - {}) from snapshot created {}",
+        snapshot.week_number,
+        snapshot.week_start.format("%B %d"),
+        snapshot.week_end.format("%B %d"),
+        snapshot.created_at
+    );
+
+    let rebuilt = SnapshotReport::build(&snapshot)?;
+    let report_data = rebuilt.report(&snapshot);
+
+    if render_report(&report_data) == snapshot.output {
+        info!("Regenerated report matches the snapshot output");
+    } else {
+        warn!("Regenerated report differs from the output recorded in the snapshot");
+    }
+
+    println!("{}", render_output(&report_data, format));
+    if let Some(path) = pdf {
+        write_pdf(&report_data, path)?;
+    }
+    Ok(())
+}
+
+/// Moves a week's report drafts through review, from `week` or last week.
+async fn run_review(cli: &Cli, week: Option<&str>, action: &ReviewCommand) -> Result<(), Box<dyn Error>> {
+    let drafts = match week {
+        Some(week) => DraftStore::new(week),
+        None => {
+            let (week_start, _) = get_previous_week_range();
+            DraftStore::for_week(&week_start, get_week_number(&week_start))
+        }
+    };
+
+    match action {
+        ReviewCommand::Status => {
+            let status = drafts.current()?;
+            if status.delivered.is_empty() {
+                println!("Week {}: draft {} is {}", drafts.week(), status.revision, status.state);
+            } else {
+                println!(
+                    "Week {}: draft {} is {}, delivered to {}",
+                    drafts.week(),
+                    status.revision,
+                    status.state,
+                    status.delivered.join(", ")
+                );
+            }
+            for event in &status.history {
+                println!("  {}", event);
+            }
+        }
+        ReviewCommand::Show => {
+            let (status, snapshot) = drafts.latest()?;
+            info!("Draft {} of week {} is {}", status.revision, drafts.week(), status.state);
+            let rebuilt = SnapshotReport::build(&snapshot)?;
+            let report_data = rebuilt.report(&snapshot);
+            println!("{}", render_output(&report_data, cli.format));
+            if let Some(path) = &cli.pdf {
+                write_pdf(&report_data, path)?;
+            }
+        }
+        ReviewCommand::Edit => {
+            let (status, mut snapshot) = drafts.latest()?;
+            if !edit_draft(&mut snapshot, status.revision)? {
+                println!("Nothing was changed, draft {} is kept", status.revision);
+                return Ok(());
             }
 
-            println!("Regards,");
+            let previous = std::mem::take(&mut snapshot.output);
+            let output = render_report(&SnapshotReport::build(&snapshot)?.report(&snapshot));
+            snapshot.output = output;
+            snapshot.created_at = Utc::now();
+            match drafts.save_draft(&snapshot, ReviewAction::Edited)? {
+                Some(revision) => {
+                    if status.state != ReviewState::Draft {
+                        info!("Draft {} was {}, the new draft has to be reviewed again", status.revision, status.state);
+                    }
+                    println!("Saved draft {} of week {}", revision, drafts.week());
+                    print!(
+                        "{}",
+                        render_diff(&previous, &snapshot.output, &format!("draft {}", status.revision), &format!("draft {}", revision))
+                    );
+                }
+                None => println!("The edits don't change the report, draft {} is kept", status.revision),
+            }
+        }
+        ReviewCommand::Diff { since } => {
+            let (status, snapshot) = drafts.latest()?;
+            let since = since.unwrap_or(status.revision - 1);
+            if since == 0 || since >= status.revision {
+                return Err(format!("Draft {} of week {} has no earlier draft {} to compare with", status.revision, drafts.week(), since).into());
+            }
+            let diff = render_diff(
+                &drafts.load(since)?.output,
+                &snapshot.output,
+                &format!("draft {}", since),
+                &format!("draft {}", status.revision),
+            );
+            if diff.is_empty() {
+                println!("Drafts {} and {} are the same", since, status.revision);
+            }
+            print!("{}", diff);
+        }
+        ReviewCommand::Submit => record_review(&drafts, ReviewAction::Submitted, None)?,
+        ReviewCommand::Approve => record_review(&drafts, ReviewAction::Approved, None)?,
+        ReviewCommand::Reject { note } => record_review(&drafts, ReviewAction::Rejected, note.clone())?,
+        ReviewCommand::Deliver => {
+            if requested_destinations(cli).is_empty() {
+                return Err("Nothing to deliver to, pass --send, --slack, --teams or --confluence".into());
+            }
+            let (_, snapshot) = drafts.latest()?;
+            let rebuilt = SnapshotReport::build(&snapshot)?;
+            let report_data = rebuilt.report(&snapshot);
+            // Without a token, only Confluence fails and the other destinations still get the report
+            let (jira_email, jira_token) = jira_credentials(false)?;
+            deliver_draft(cli, &drafts, &report_data, &jira_email, &jira_token).await?;
         }
     }
+    Ok(())
+}
 
+fn record_review(drafts: &DraftStore, action: ReviewAction, note: Option<String>) -> Result<(), Box<dyn Error>> {
+    let status = drafts.record(action, note)?;
+    println!("Draft {} of week {} is now {}", status.revision, drafts.week(), status.state);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_cache(record_dir: Option<PathBuf>) -> JiraCache {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/jira");
+        JiraCache::new(dir, Duration::hours(24), true, record_dir).unwrap()
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_fixture_offline() {
+        // The fixture is older than the TTL, offline mode uses it without contacting JIRA
+        let issue = load_jira_issue("OPS-1002", &fixture_cache(None), None, "", "").await.unwrap();
+
+        assert_eq!(issue.fields.summary.as_deref(), Some("Sugar Market EU Services unavailable"));
+        let description = issue.fields.description.as_deref().unwrap();
+        assert_eq!(extract_time_from_description(description), (Some("10:05".to_string()), Some("11:08".to_string())));
+        assert_eq!(extract_rca_sections(description).rca.as_deref(), Some("nginx crashed on both nodes"));
+    }
+
+    #[test]
+    fn rca_sections_end_at_the_next_capitalized_heading() {
+        let description = "RCA:\nroot cause was: config drift\nnginx reloaded with the old config\nPreventative Measures:\ncheck: config hashes\nOwner: ops";
+        let sections = extract_rca_sections(description);
+        assert_eq!(sections.rca.as_deref(), Some("root cause was: config drift\nnginx reloaded with the old config"));
+        assert_eq!(sections.preventative_measures.as_deref(), Some("check: config hashes"));
+    }
+
+    #[tokio::test]
+    async fn offline_mode_fails_without_a_fixture() {
+        let error = load_jira_issue("OPS-9999", &fixture_cache(None), None, "", "").await.unwrap_err();
+        assert_eq!(error.to_string(), "OPS-9999 is not in the JIRA cache (offline mode)");
+    }
+
+    #[tokio::test]
+    async fn replayed_fixture_is_recorded_again() {
+        let record_dir = env::temp_dir().join(format!("weekly-status-report-fixtures-{}", std::process::id()));
+        load_jira_issue("OPS-1002", &fixture_cache(Some(record_dir.clone())), None, "", "").await.unwrap();
+
+        let recorded = std::fs::read_to_string(record_dir.join("OPS-1002.json")).unwrap();
+        std::fs::remove_dir_all(&record_dir).unwrap();
+        let recorded = JiraIssue::from_payload(&serde_json::from_str::<jira_cache::JiraCacheEntry>(&recorded).unwrap().payload).unwrap();
+        assert_eq!(recorded.fields.updated.as_deref(), Some("2026-10-15T10:00:00.000+0000"));
+    }
+}
@@ -0,0 +1,152 @@
+use log::warn;
+use regex::Regex;
+use std::path::PathBuf;
+
+use crate::actions::{incident_label, PreventativeAction};
+use crate::report::{issue_url, Report, ReportIncident};
+use crate::OutageRecord;
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn link(text: &str, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("[{}]({})", text, url),
+        None => text.to_string(),
+    }
+}
+
+/// Turns mentions of the incident's own ticket key in its narrative into links.
+fn link_ticket_mentions(body: &str, incident: &ReportIncident) -> String {
+    let (Some(key), Some(url)) = (&incident.jira_key, incident.ticket_url()) else {
+        return body.to_string();
+    };
+    let pattern = Regex::new(&format!(r"\b{}\b", regex::escape(key))).expect("ticket pattern is valid");
+    pattern.replace_all(body, format!("[{}]({})", key, url).as_str()).into_owned()
+}
+
+fn action_line(record: &OutageRecord, action: &PreventativeAction) -> String {
+    let mut line = format!(
+        "- **{}:** {}",
+        incident_label(record),
+        link(&action.key, issue_url(&record.ticket, &action.key).as_deref())
+    );
+    if let Some(summary) = &action.summary {
+        line.push_str(&format!(" {}", summary.trim()));
+    }
+    line.push_str(&action.progress());
+    line
+}
+
+fn push_metrics(markdown: &mut String, report: &Report) {
+    markdown.push_str("## Key Metrics\n\n");
+    markdown.push_str("| Metric | This week | Trend |\n");
+    markdown.push_str("| --- | --- | --- |\n");
+    for row in &report.metrics {
+        markdown.push_str(&format!(
+            "| {} | {} | {} |\n",
+            row.label,
+            table_cell(&row.value),
+            table_cell(row.trend.as_deref().unwrap_or("-"))
+        ));
+    }
+    markdown.push('\n');
+}
+
+fn push_charts(markdown: &mut String, charts: &[(&str, PathBuf)]) {
+    if charts.is_empty() {
+        return;
+    }
+
+    markdown.push_str("## Charts\n\n");
+    for (title, path) in charts {
+        // Forward slashes so the links also work when rendered on Windows
+        let path = path.to_string_lossy().replace('\\', "/");
+        markdown.push_str(&format!("![{}]({})\n\n", title, path));
+    }
+}
+
+fn push_actions(markdown: &mut String, report: &Report) {
+    let actions = &report.actions;
+    if actions.is_empty() {
+        return;
+    }
+
+    markdown.push_str("## Preventative Actions\n\n");
+    if actions.incidents.is_empty() {
+        markdown.push_str("None for this week's incidents\n");
+    }
+    for (record, incident_actions) in &actions.incidents {
+        if incident_actions.is_empty() {
+            markdown.push_str(&format!("- **{}:** no follow-up linked in JIRA\n", incident_label(record)));
+        }
+        for action in incident_actions {
+            markdown.push_str(&format!("{}\n", action_line(record, action)));
+        }
+    }
+    markdown.push('\n');
+
+    if !actions.carried_over.is_empty() {
+        markdown.push_str("### Open Actions Carried Over from Previous Weeks\n\n");
+        for (record, action) in &actions.carried_over {
+            markdown.push_str(&format!("{}\n", action_line(record, action)));
+        }
+        markdown.push('\n');
+    }
+}
+
+fn push_incident(markdown: &mut String, incident: &ReportIncident) {
+    markdown.push_str(&format!("### {}\n\n", incident.header()));
+
+    let mut details = Vec::new();
+    if let Some(key) = &incident.jira_key {
+        details.push(format!("**Ticket:** {}", link(key, incident.ticket_url())));
+    }
+    if let Some(recurrence) = incident.recurrence {
+        details.push(format!("**Recurring:** {}", recurrence));
+    }
+    if !details.is_empty() {
+        // Two trailing spaces keep the details on separate lines
+        markdown.push_str(&format!("{}\n\n", details.join("  \n")));
+    }
+
+    markdown.push_str(&format!("{}\n\n", link_ticket_mentions(&incident.body(), incident)));
+}
+
+/// Renders the report as Markdown for Confluence and Slack, from the same data as the text report.
+/// `charts` are the titles and paths of SVG charts written alongside it.
+pub fn render_markdown(report: &Report, charts: &[(&str, PathBuf)]) -> String {
+    let mut markdown = String::new();
+
+    markdown.push_str(&format!("# {}\n\n", report.title()));
+    markdown.push_str(&format!(
+        "**Week {}** ({} - {}), all times UTC\n\n",
+        report.week_number,
+        report.week_start.format("%B %d"),
+        report.week_end.format("%B %d")
+    ));
+
+    if let Some(summary) = report.ai.and_then(|ai| ai.summary.as_deref()) {
+        markdown.push_str(&format!("{}\n\n", summary));
+    }
+
+    push_metrics(&mut markdown, report);
+    push_charts(&mut markdown, charts);
+    push_actions(&mut markdown, report);
+
+    if report.ai.is_some_and(|ai| ai.narratives.is_empty()) && !report.incidents.is_empty() {
+        warn!("This AI report predates per-incident narratives, using the standard entries in Markdown");
+    }
+
+    markdown.push_str("## Incidents\n\n");
+    if report.incidents.is_empty() {
+        markdown.push_str("No incidents this week.\n\n");
+    }
+    for incident in &report.incidents {
+        push_incident(&mut markdown, incident);
+    }
+
+    markdown.push_str("Regards,\n");
+    markdown
+}
@@ -0,0 +1,292 @@
+use chrono::{NaiveTime, Timelike};
+use log::warn;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::trends::{describe_change, Kpi, TrendComparison};
+use crate::{extract_jira_key, extract_time_from_description, parse_date, parse_duration_to_minutes, JiraIssue, OutageRecord};
+
+#[derive(Debug, Clone, Default)]
+pub struct WeeklyMetrics {
+    pub incident_count: usize,
+    pub by_severity: BTreeMap<String, usize>,
+    pub total_downtime_minutes: i32,
+    pub mean_time_to_resolve: Option<f64>,
+    pub median_time_to_resolve: Option<f64>,
+    pub longest_incident: Option<LongestIncident>,
+    pub mean_time_to_detect: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LongestIncident {
+    pub service: String,
+    pub date: String,
+    pub minutes: i32,
+}
+
+fn duration_minutes(record: &OutageRecord) -> Option<i32> {
+    // Blank durations are unknown rather than the 5 minute default used for time estimates
+    if record.duration.trim().is_empty() {
+        return None;
+    }
+    Some(parse_duration_to_minutes(&record.duration))
+}
+
+fn detection_minutes(record: &OutageRecord, jira_details: &HashMap<String, JiraIssue>) -> Option<i32> {
+    let detected = record.detected.as_deref()?.trim();
+    let detected = NaiveTime::parse_from_str(detected, "%H:%M").ok()?;
+
+    // Only a start time recorded in JIRA is trustworthy here, the estimated ones are guesses
+    let jira_key = extract_jira_key(&record.ticket)?;
+    let description = jira_details.get(&jira_key)?.fields.description.as_deref()?;
+    let (start, _) = extract_time_from_description(description);
+    let start = NaiveTime::parse_from_str(&start?, "%H:%M").ok()?;
+
+    let minutes = (detected - start).num_minutes() as i32;
+    if minutes >= 0 {
+        return Some(minutes);
+    }
+    // Detected after midnight for an incident that started late the previous day
+    if start.hour() >= 20 && detected.hour() < 4 {
+        return Some(minutes + 24 * 60);
+    }
+    warn!(
+        "{} {}: detected at {} is before the start at {}, leaving it out of the time to detect",
+        record.date.trim(),
+        record.service.trim(),
+        detected.format("%H:%M"),
+        start.format("%H:%M")
+    );
+    None
+}
+
+fn median(values: &mut [i32]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) as f64 / 2.0)
+    } else {
+        Some(values[mid] as f64)
+    }
+}
+
+fn mean(values: &[i32]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<i32>() as f64 / values.len() as f64)
+}
+
+pub fn compute_weekly_metrics(outages: &[OutageRecord], jira_details: &HashMap<String, JiraIssue>) -> WeeklyMetrics {
+    let mut by_severity = BTreeMap::new();
+    let mut resolve_times = Vec::new();
+    let mut detect_times = Vec::new();
+    let mut longest_incident: Option<LongestIncident> = None;
+
+    for record in outages {
+        let severity = if record.severity.trim().is_empty() {
+            "Unspecified".to_string()
+        } else {
+            record.severity.trim().to_string()
+        };
+        *by_severity.entry(severity).or_insert(0) += 1;
+
+        if let Some(minutes) = duration_minutes(record) {
+            resolve_times.push(minutes);
+            if longest_incident.as_ref().is_none_or(|l| minutes > l.minutes) {
+                longest_incident = Some(LongestIncident {
+                    service: record.service.clone(),
+                    date: record.date.clone(),
+                    minutes,
+                });
+            }
+        }
+
+        if let Some(minutes) = detection_minutes(record, jira_details) {
+            detect_times.push(minutes);
+        }
+    }
+
+    WeeklyMetrics {
+        incident_count: outages.len(),
+        by_severity,
+        total_downtime_minutes: resolve_times.iter().sum(),
+        mean_time_to_resolve: mean(&resolve_times),
+        median_time_to_resolve: median(&mut resolve_times),
+        longest_incident,
+        mean_time_to_detect: mean(&detect_times),
+    }
+}
+
+/// Downtime minutes per service, most first. Incidents without a duration don't count.
+pub fn downtime_by_service<'a>(outages: impl IntoIterator<Item = &'a OutageRecord>) -> Vec<(String, i32)> {
+    let mut by_service: BTreeMap<String, i32> = BTreeMap::new();
+    for record in outages {
+        if let Some(minutes) = duration_minutes(record) {
+            *by_service.entry(record.service.trim().to_string()).or_insert(0) += minutes;
+        }
+    }
+    let mut downtime: Vec<(String, i32)> = by_service.into_iter().collect();
+    downtime.sort_by_key(|(_, minutes)| std::cmp::Reverse(*minutes));
+    downtime
+}
+
+pub fn format_minutes(minutes: f64) -> String {
+    let minutes = minutes.round() as i64;
+    if minutes >= 60 {
+        format!("{}h {}min", minutes / 60, minutes % 60)
+    } else {
+        format!("{}min", minutes)
+    }
+}
+
+/// One KPI as shown in the summary, e.g. "Total downtime" with its value and trend.
+#[derive(Debug, Clone)]
+pub struct MetricRow {
+    pub label: &'static str,
+    pub value: String,
+    pub trend: Option<String>,
+}
+
+pub fn metric_rows(metrics: &WeeklyMetrics, trends: Option<&TrendComparison>) -> Vec<MetricRow> {
+    kpi_rows(metrics, |kpi| trends.and_then(|t| t.describe(kpi, kpi.value(metrics))))
+}
+
+/// KPI rows for a month, with the change from the previous month as the trend.
+pub fn monthly_metric_rows(metrics: &WeeklyMetrics, previous_month: &WeeklyMetrics) -> Vec<MetricRow> {
+    kpi_rows(metrics, |kpi| describe_change(kpi, kpi.value(metrics), kpi.value(previous_month), "last month"))
+}
+
+fn kpi_rows(metrics: &WeeklyMetrics, trend: impl Fn(Kpi) -> Option<String>) -> Vec<MetricRow> {
+    let mut rows = Vec::new();
+    let row = |label: &'static str, value: String, kpi: Kpi| MetricRow {
+        label,
+        value,
+        trend: trend(kpi),
+    };
+
+    let severities = metrics
+        .by_severity
+        .iter()
+        .map(|(severity, count)| format!("{}: {}", severity, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let incidents = if severities.is_empty() {
+        metrics.incident_count.to_string()
+    } else {
+        format!("{} ({})", metrics.incident_count, severities)
+    };
+    rows.push(row("Incidents", incidents, Kpi::IncidentCount));
+
+    rows.push(row(
+        "Total downtime",
+        format_minutes(metrics.total_downtime_minutes as f64),
+        Kpi::TotalDowntime,
+    ));
+
+    if let Some(mttr) = metrics.mean_time_to_resolve {
+        rows.push(row("Mean time to resolve", format_minutes(mttr), Kpi::MeanTimeToResolve));
+    }
+    if let Some(median) = metrics.median_time_to_resolve {
+        rows.push(row("Median time to resolve", format_minutes(median), Kpi::MedianTimeToResolve));
+    }
+    if let Some(longest) = &metrics.longest_incident {
+        let date = parse_date(&longest.date)
+            .map(|d| d.format("%B %d").to_string())
+            .unwrap_or_else(|| longest.date.clone());
+        rows.push(row(
+            "Longest incident",
+            format!("{} - {} ({})", format_minutes(longest.minutes as f64), longest.service, date),
+            Kpi::LongestIncident,
+        ));
+    }
+    if let Some(mttd) = metrics.mean_time_to_detect {
+        rows.push(row("Mean time to detect", format_minutes(mttd), Kpi::MeanTimeToDetect));
+    }
+
+    rows
+}
+
+pub fn format_metrics_summary(rows: &[MetricRow]) -> String {
+    rows.iter()
+        .map(|row| match &row.trend {
+            Some(trend) => format!("{}: {} [{}]", row.label, row.value, trend),
+            None => format!("{}: {}", row.label, row.value),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::outage;
+
+    fn incident(ticket: usize, duration: &str, severity: &str, detected: Option<&str>) -> OutageRecord {
+        OutageRecord {
+            duration: duration.to_string(),
+            severity: severity.to_string(),
+            detected: detected.map(str::to_string),
+            ..outage(ticket, "Nginx failed")
+        }
+    }
+
+    fn jira(ticket: usize, description: &str) -> (String, JiraIssue) {
+        let key = format!("OPS-{}", ticket);
+        let payload = serde_json::json!({ "key": key, "fields": { "description": description } }).to_string();
+        (key, JiraIssue::from_payload(&payload).unwrap())
+    }
+
+    #[test]
+    fn computes_the_weekly_kpis() {
+        let outages = vec![
+            incident(1, "15", "S2", Some("10:20")),
+            incident(2, "1h0m", "S1", None),
+            incident(3, "", "", None),
+            incident(4, "45", " S2 ", Some("09:00")),
+        ];
+        let jira_details = HashMap::from([jira(1, "Outage 10:05 - 10:20"), jira(4, "Outage 08:30 - 09:15")]);
+        let metrics = compute_weekly_metrics(&outages, &jira_details);
+
+        assert_eq!(metrics.incident_count, 4);
+        let by_severity: Vec<(&str, usize)> = metrics.by_severity.iter().map(|(s, n)| (s.as_str(), *n)).collect();
+        assert_eq!(by_severity, vec![("S1", 1), ("S2", 2), ("Unspecified", 1)]);
+        // The blank duration is unknown, not 0 or the 5 minute estimate
+        assert_eq!(metrics.total_downtime_minutes, 120);
+        assert_eq!(metrics.mean_time_to_resolve, Some(40.0));
+        assert_eq!(metrics.median_time_to_resolve, Some(45.0));
+        assert_eq!(metrics.longest_incident.map(|longest| longest.minutes), Some(60));
+        assert_eq!(metrics.mean_time_to_detect, Some(22.5));
+
+        let empty = compute_weekly_metrics(&[], &HashMap::new());
+        assert_eq!((empty.incident_count, empty.mean_time_to_resolve, empty.median_time_to_resolve), (0, None, None));
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [30, 10, 20]), Some(20.0));
+        assert_eq!(median(&mut [30, 10, 40, 20]), Some(25.0));
+    }
+
+    #[test]
+    fn detection_only_wraps_past_midnight_for_late_night_incidents() {
+        let detect = |start: &str, detected: &str| {
+            let jira_details = HashMap::from([jira(1, &format!("Outage {} - 23:59", start))]);
+            detection_minutes(&incident(1, "30", "S2", Some(detected)), &jira_details)
+        };
+
+        assert_eq!(detect("10:05", "10:20"), Some(15));
+        assert_eq!(detect("23:50", "00:20"), Some(30));
+        assert_eq!(detect("20:00", "03:59"), Some(479));
+        // Detected before the start on the same day is a data entry mistake, not a wrap
+        assert_eq!(detect("10:05", "09:00"), None);
+        assert_eq!(detect("19:59", "00:20"), None);
+        assert_eq!(detect("23:50", "04:00"), None);
+
+        // Times estimated without JIRA aren't used
+        assert_eq!(detection_minutes(&incident(2, "30", "S2", Some("10:20")), &HashMap::new()), None);
+    }
+}
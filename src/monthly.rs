@@ -0,0 +1,211 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use std::collections::HashMap;
+
+use crate::metrics::{
+    compute_weekly_metrics, downtime_by_service, format_metrics_summary, format_minutes, monthly_metric_rows, MetricRow,
+    WeeklyMetrics,
+};
+use crate::{extract_jira_key, format_outage_header, get_week_number, outages_in_range, JiraIssue, OutageRecord};
+
+/// A report week within the month, cut off at the month's first and last day.
+pub struct MonthWeek {
+    pub week_number: u32,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub metrics: WeeklyMetrics,
+}
+
+impl MonthWeek {
+    pub fn label(&self) -> String {
+        format!("Week {} ({} - {})", self.week_number, self.start.format("%b %d"), self.end.format("%b %d"))
+    }
+}
+
+/// The reliability report for a calendar month, compared with the month before.
+pub struct MonthlyReport {
+    pub month_start: NaiveDate,
+    pub month_end: NaiveDate,
+    pub metrics: Vec<MetricRow>,
+    pub weeks: Vec<MonthWeek>,
+    pub downtime: Vec<(String, i32)>,
+    pub outages: Vec<OutageRecord>,
+}
+
+/// First and last day of `year`-`month`.
+pub fn month_range(year: i32, month: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some((start, next - Duration::days(1)))
+}
+
+/// First and last day of the month before this one.
+pub fn previous_month_range() -> (NaiveDate, NaiveDate) {
+    let this_month = Local::now().date_naive().with_day(1).expect("the first is a valid day");
+    let last_day = this_month - Duration::days(1);
+    (last_day.with_day(1).expect("the first is a valid day"), last_day)
+}
+
+impl MonthlyReport {
+    pub fn build(
+        all_outages: &[OutageRecord],
+        jira_details: &HashMap<String, JiraIssue>,
+        month_start: NaiveDate,
+        month_end: NaiveDate,
+    ) -> Self {
+        let outages = outages_in_range(all_outages, &month_start, &month_end);
+        let previous_end = month_start - Duration::days(1);
+        let previous_start = previous_end.with_day(1).expect("the first is a valid day");
+        let previous = outages_in_range(all_outages, &previous_start, &previous_end);
+        let metrics = monthly_metric_rows(
+            &compute_weekly_metrics(&outages, jira_details),
+            &compute_weekly_metrics(&previous, jira_details),
+        );
+
+        // Report weeks run Sunday to Saturday
+        let mut weeks = Vec::new();
+        let mut week_start = month_start - Duration::days(month_start.weekday().num_days_from_sunday() as i64);
+        while week_start <= month_end {
+            let start = week_start.max(month_start);
+            let end = (week_start + Duration::days(6)).min(month_end);
+            weeks.push(MonthWeek {
+                week_number: get_week_number(&week_start),
+                start,
+                end,
+                metrics: compute_weekly_metrics(&outages_in_range(&outages, &start, &end), jira_details),
+            });
+            week_start += Duration::weeks(1);
+        }
+
+        MonthlyReport {
+            month_start,
+            month_end,
+            metrics,
+            weeks,
+            downtime: downtime_by_service(&outages),
+            outages,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("Monthly Reliability Report – {}", self.month_start.format("%B %Y"))
+    }
+
+    pub fn period(&self) -> String {
+        format!("{} - {}", self.month_start.format("%B %d"), self.month_end.format("%B %d, %Y"))
+    }
+}
+
+/// A one-line summary of an incident, e.g. "October 14 (63min) Billing (S1) OPS-1002: Nginx failed".
+pub fn incident_line(record: &OutageRecord) -> String {
+    let mut line = format_outage_header(record, None, None);
+    if let Some(key) = extract_jira_key(&record.ticket) {
+        line.push_str(&format!(" {}", key));
+    }
+    let cause = record.cause.trim();
+    if !cause.is_empty() {
+        line.push_str(&format!(": {}", cause));
+    }
+    line
+}
+
+/// Renders the monthly report as plain text.
+pub fn render_monthly_report(report: &MonthlyReport) -> String {
+    let mut sections = vec![
+        format!("{}\n{}, all times UTC", report.title(), report.period()),
+        format!("Key metrics\n{}", format_metrics_summary(&report.metrics)),
+    ];
+
+    let weeks: Vec<String> = report
+        .weeks
+        .iter()
+        .map(|week| {
+            format!(
+                "{}: {} incident(s), {} downtime",
+                week.label(),
+                week.metrics.incident_count,
+                format_minutes(week.metrics.total_downtime_minutes as f64)
+            )
+        })
+        .collect();
+    sections.push(format!("Weekly breakdown\n{}", weeks.join("\n")));
+
+    if !report.downtime.is_empty() {
+        let downtime: Vec<String> = report
+            .downtime
+            .iter()
+            .map(|(service, minutes)| format!("{}: {}", service, format_minutes(*minutes as f64)))
+            .collect();
+        sections.push(format!("Downtime by service\n{}", downtime.join("\n")));
+    }
+
+    let incidents = if report.outages.is_empty() {
+        "No incidents this month.".to_string()
+    } else {
+        report.outages.iter().map(incident_line).collect::<Vec<_>>().join("\n")
+    };
+    sections.push(format!("Incidents\n{}", incidents));
+
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::outage;
+
+    fn on(date: &str, ticket: usize, duration: &str) -> OutageRecord {
+        OutageRecord {
+            date: date.to_string(),
+            duration: duration.to_string(),
+            ..outage(ticket, "Nginx failed")
+        }
+    }
+
+    #[test]
+    fn month_ranges_cover_whole_months() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(month_range(2026, 2), Some((day(2026, 2, 1), day(2026, 2, 28))));
+        assert_eq!(month_range(2026, 12), Some((day(2026, 12, 1), day(2026, 12, 31))));
+        assert_eq!(month_range(2026, 13), None);
+    }
+
+    #[test]
+    fn aggregates_the_month_by_week_and_against_the_previous_month() {
+        let outages = vec![
+            on("30/Sep/26", 1, "10"),
+            on("01/Oct/26", 2, "20"),
+            on("12/Oct/26", 3, "30"),
+            on("31/Oct/26", 4, "40"),
+            on("01/Nov/26", 5, "50"),
+        ];
+        let (start, end) = month_range(2026, 10).unwrap();
+        let report = MonthlyReport::build(&outages, &HashMap::new(), start, end);
+
+        assert_eq!(report.outages.len(), 3);
+        assert_eq!(report.downtime, vec![("Sales-I US".to_string(), 90)]);
+        assert_eq!(report.metrics[0].value, "3 (S2: 3)");
+        assert_eq!(report.metrics[0].trend.as_deref(), Some("▲ +2 (+200%) vs last month"));
+
+        // October 2026 starts on a Thursday and ends on a Saturday
+        let weeks: Vec<(String, usize)> =
+            report.weeks.iter().map(|week| (week.label(), week.metrics.incident_count)).collect();
+        assert_eq!(
+            weeks,
+            vec![
+                ("Week 39 (Oct 01 - Oct 03)".to_string(), 1),
+                ("Week 40 (Oct 04 - Oct 10)".to_string(), 0),
+                ("Week 41 (Oct 11 - Oct 17)".to_string(), 1),
+                ("Week 42 (Oct 18 - Oct 24)".to_string(), 0),
+                ("Week 43 (Oct 25 - Oct 31)".to_string(), 1),
+            ]
+        );
+
+        let text = render_monthly_report(&report);
+        assert!(text.starts_with("Monthly Reliability Report – October 2026\nOctober 01 - October 31, 2026"));
+        assert!(text.contains("October 12 (30min) Sales-I US (S2) OPS-3: Nginx failed"));
+    }
+}
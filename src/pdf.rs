@@ -0,0 +1,623 @@
+use log::{info, warn};
+use printpdf::path::PaintMode;
+use printpdf::{
+    image_crate, BuiltinFont, Color, Image, ImageTransform, IndirectFontRef, Line, Mm, PdfDocument,
+    PdfDocumentReference, PdfLayerIndex, PdfLayerReference, PdfPageIndex, Point, Rect, Rgb,
+};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::actions::{incident_label, PreventativeAction};
+use crate::charts::{self, severity_history, timeline_rows, MAX_DOWNTIME_BARS, SEVERITY_WEEKS};
+use crate::metrics::{downtime_by_service, format_minutes, MetricRow};
+use crate::monthly::{incident_line, MonthlyReport};
+use crate::report::{Report, ReportIncident};
+use crate::OutageRecord;
+
+// A4 portrait, in millimetres
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 18.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const FOOTER_Y: f32 = 10.0;
+const LOGO_HEIGHT: f32 = 14.0;
+
+const PT_TO_MM: f32 = 0.3528;
+// Average Helvetica glyph width as a fraction of the font size, for wrapping
+const CHAR_WIDTH: f32 = 0.5;
+const BOLD_CHAR_WIDTH: f32 = 0.55;
+
+const TEXT: (u8, u8, u8) = (0x22, 0x22, 0x22);
+const MUTED: (u8, u8, u8) = (0x77, 0x77, 0x77);
+const RULE: (u8, u8, u8) = (0xdd, 0xdd, 0xdd);
+const TABLE_HEADER: (u8, u8, u8) = (0xf0, 0xf0, 0xf0);
+const BAR: (u8, u8, u8) = (0x2e, 0x86, 0xc1);
+const RECURRING: (u8, u8, u8) = (0xe6, 0x7e, 0x22);
+
+/// Same colours as the severity badges in the HTML email and the SVG charts.
+fn severity_color(severity: &str) -> (u8, u8, u8) {
+    let hex = charts::severity_color(severity).trim_start_matches('#');
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+    (channel(0), channel(2), channel(4))
+}
+
+/// Mixes a colour halfway to white, for bars with estimated times.
+fn lighten((r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+    let mix = |c: u8| c + (255 - c) / 2;
+    (mix(r), mix(g), mix(b))
+}
+
+fn color((r, g, b): (u8, u8, u8)) -> Color {
+    Color::Rgb(Rgb::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, None))
+}
+
+/// The built-in PDF fonts only cover Windows-1252, so anything outside it is replaced.
+fn pdf_text(text: &str) -> String {
+    const WIN_ANSI_EXTRAS: &str = "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ";
+    text.replace('▲', "up")
+        .replace('▼', "down")
+        .replace('→', "->")
+        .chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c,
+            '\t' => ' ',
+            _ if WIN_ANSI_EXTRAS.contains(c) => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+/// Greedy word wrap using an average glyph width, which is close enough for Helvetica.
+fn wrap(text: &str, size: f32, bold: bool, width: f32) -> Vec<String> {
+    let char_width = size * PT_TO_MM * if bold { BOLD_CHAR_WIDTH } else { CHAR_WIDTH };
+    let max_chars = ((width / char_width) as usize).max(1);
+
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn line_height(size: f32) -> f32 {
+    size * PT_TO_MM * 1.4
+}
+
+/// Tracks the current page and how far down it we've written, starting a new page when needed.
+struct Layout {
+    doc: PdfDocumentReference,
+    pages: Vec<(PdfPageIndex, PdfLayerIndex)>,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+
+impl Layout {
+    fn new(title: &str) -> Result<Self, Box<dyn Error>> {
+        let (doc, page, layer_index) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+        let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+        let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+        let layer = doc.get_page(page).get_layer(layer_index);
+        Ok(Layout {
+            doc,
+            pages: vec![(page, layer_index)],
+            layer,
+            regular,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer_index) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+        self.layer = self.doc.get_page(page).get_layer(layer_index);
+        self.pages.push((page, layer_index));
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Starts a new page unless `height` still fits above the bottom margin.
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn font(&self, bold: bool) -> &IndirectFontRef {
+        if bold {
+            &self.bold
+        } else {
+            &self.regular
+        }
+    }
+
+    fn text_at(&self, text: &str, size: f32, bold: bool, rgb: (u8, u8, u8), x: f32, y: f32) {
+        self.layer.set_fill_color(color(rgb));
+        self.layer.use_text(pdf_text(text), size, Mm(x), Mm(y), self.font(bold));
+    }
+
+    /// Writes wrapped text at `indent` from the left margin, breaking pages between lines.
+    fn paragraph(&mut self, text: &str, size: f32, bold: bool, rgb: (u8, u8, u8), indent: f32) {
+        let height = line_height(size);
+        for line in wrap(text, size, bold, CONTENT_WIDTH - indent) {
+            self.ensure(height);
+            self.y -= height;
+            self.text_at(&line, size, bold, rgb, MARGIN + indent, self.y + height * 0.25);
+        }
+    }
+
+    fn heading(&mut self, text: &str, size: f32) {
+        // Keep a heading with at least a few lines of what follows it
+        self.ensure(line_height(size) + 20.0);
+        self.gap(3.0);
+        self.paragraph(text, size, true, TEXT, 0.0);
+        self.gap(1.5);
+    }
+
+    fn rect(&self, x: f32, y: f32, width: f32, height: f32, rgb: (u8, u8, u8)) {
+        self.layer.set_fill_color(color(rgb));
+        self.layer
+            .add_rect(Rect::new(Mm(x), Mm(y), Mm(x + width), Mm(y + height)).with_mode(PaintMode::Fill));
+    }
+
+    fn line(&self, from: (f32, f32), to: (f32, f32)) {
+        self.layer.set_outline_color(color(RULE));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(from.0), Mm(from.1)), false),
+                (Point::new(Mm(to.0), Mm(to.1)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    fn rule(&self, y: f32) {
+        self.line((MARGIN, y), (MARGIN + CONTENT_WIDTH, y));
+    }
+}
+
+/// Loads the logo from `PDF_LOGO` (PNG or JPEG), if one is configured and readable.
+fn load_logo() -> Option<Image> {
+    let path = env::var("PDF_LOGO").ok().filter(|path| !path.trim().is_empty())?;
+    match image_crate::open(&path) {
+        // PDFs don't support transparency in images this way, so flatten it
+        Ok(logo) => Some(Image::from_dynamic_image(&image_crate::DynamicImage::ImageRgb8(logo.to_rgb8()))),
+        Err(e) => {
+            warn!("Could not load the PDF logo {}, leaving it out: {}", path, e);
+            None
+        }
+    }
+}
+
+fn push_header(layout: &mut Layout, title: &str, subtitle: &str, summary: Option<&str>) {
+    let top = layout.y;
+    let mut title_width = CONTENT_WIDTH;
+    if let Some(logo) = load_logo() {
+        // Scale the logo to a fixed height in the top right corner
+        let pixels = logo.image.height.0 as f32;
+        let dpi = pixels * 25.4 / LOGO_HEIGHT;
+        let width = logo.image.width.0 as f32 * 25.4 / dpi;
+        logo.add_to_layer(
+            layout.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(MARGIN + CONTENT_WIDTH - width)),
+                translate_y: Some(Mm(top - LOGO_HEIGHT)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+        title_width -= width + 4.0;
+    }
+
+    // The title wraps short of the logo
+    let height = line_height(20.0);
+    for line in wrap(title, 20.0, true, title_width) {
+        layout.y -= height;
+        layout.text_at(&line, 20.0, true, TEXT, MARGIN, layout.y + height * 0.25);
+    }
+    layout.paragraph(subtitle, 10.0, false, MUTED, 0.0);
+    layout.y = layout.y.min(top - LOGO_HEIGHT) - 3.0;
+    layout.rule(layout.y);
+    layout.gap(4.0);
+
+    if let Some(summary) = summary {
+        layout.paragraph(summary, 10.0, false, TEXT, 0.0);
+        layout.gap(2.0);
+    }
+}
+
+fn push_metrics(layout: &mut Layout, metrics: &[MetricRow], period: &str) {
+    let columns = [("Metric", 45.0), (period, 55.0), ("Trend", CONTENT_WIDTH - 100.0)];
+    let rows = metrics
+        .iter()
+        .map(|row| vec![row.label.to_string(), row.value.clone(), row.trend.clone().unwrap_or_else(|| "-".to_string())])
+        .collect();
+    push_table(layout, "Key Metrics", &columns, rows);
+}
+
+/// A table with a shaded header row, wrapping cells to their column's width in mm.
+fn push_table(layout: &mut Layout, heading: &str, columns: &[(&str, f32)], cells: Vec<Vec<String>>) {
+    const SIZE: f32 = 9.0;
+    const PADDING: f32 = 1.5;
+
+    layout.heading(heading, 13.0);
+    let mut rows = vec![(true, columns.iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>())];
+    rows.extend(cells.into_iter().map(|cells| (false, cells)));
+
+    for (header, cells) in rows {
+        let wrapped: Vec<Vec<String>> = cells
+            .iter()
+            .zip(columns)
+            .map(|(cell, (_, width))| wrap(cell, SIZE, header, width - 2.0 * PADDING))
+            .collect();
+        let lines = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+        let height = lines as f32 * line_height(SIZE) + 2.0 * PADDING;
+
+        layout.ensure(height);
+        layout.y -= height;
+        if header {
+            layout.rect(MARGIN, layout.y, CONTENT_WIDTH, height, TABLE_HEADER);
+        }
+        let mut x = MARGIN;
+        for (cell, (_, width)) in wrapped.iter().zip(columns) {
+            for (index, line) in cell.iter().enumerate() {
+                let baseline = layout.y + height - PADDING - (index as f32 + 0.75) * line_height(SIZE);
+                layout.text_at(line, SIZE, header, TEXT, x + PADDING, baseline);
+            }
+            x += width;
+        }
+        layout.rule(layout.y);
+    }
+    layout.gap(4.0);
+}
+
+fn push_downtime_chart(layout: &mut Layout, downtime: &[(String, i32)]) {
+    const SIZE: f32 = 8.5;
+    const BAR_HEIGHT: f32 = 5.0;
+    const LABEL_WIDTH: f32 = 55.0;
+    const VALUE_WIDTH: f32 = 20.0;
+
+    if downtime.is_empty() {
+        return;
+    }
+
+    layout.heading("Downtime by Service", 13.0);
+    let longest = downtime.iter().map(|(_, minutes)| *minutes).max().unwrap_or(0).max(1) as f32;
+    let scale = (CONTENT_WIDTH - LABEL_WIDTH - VALUE_WIDTH) / longest;
+
+    for (service, minutes) in downtime.iter().take(MAX_DOWNTIME_BARS) {
+        layout.ensure(BAR_HEIGHT + 2.0);
+        layout.y -= BAR_HEIGHT + 2.0;
+        let label = wrap(service, SIZE, false, LABEL_WIDTH - 2.0).into_iter().next().unwrap_or_default();
+        layout.text_at(&label, SIZE, false, TEXT, MARGIN, layout.y + 1.2);
+        let width = (*minutes as f32 * scale).max(0.5);
+        layout.rect(MARGIN + LABEL_WIDTH, layout.y, width, BAR_HEIGHT, BAR);
+        layout.text_at(
+            &format_minutes(*minutes as f64),
+            SIZE,
+            false,
+            MUTED,
+            MARGIN + LABEL_WIDTH + width + 1.5,
+            layout.y + 1.2,
+        );
+    }
+    if downtime.len() > MAX_DOWNTIME_BARS {
+        layout.paragraph(&format!("and {} more services", downtime.len() - MAX_DOWNTIME_BARS), SIZE, false, MUTED, 0.0);
+    }
+    layout.gap(4.0);
+}
+
+/// Gantt-style timeline of the week's incidents, one row per service, as in the SVG chart.
+fn push_timeline_chart(layout: &mut Layout, report: &Report) {
+    const SIZE: f32 = 8.0;
+    const ROW_HEIGHT: f32 = 5.5;
+    const LABEL_WIDTH: f32 = 42.0;
+
+    let rows = timeline_rows(report);
+    if rows.is_empty() {
+        return;
+    }
+
+    layout.heading("Incident Timeline", 13.0);
+    let days = (report.week_end - report.week_start).num_days() + 1;
+    let plot_width = CONTENT_WIDTH - LABEL_WIDTH;
+    let day_width = plot_width / days as f32;
+    // Keep the day labels with at least the first row
+    layout.ensure(2.0 * ROW_HEIGHT);
+    layout.y -= ROW_HEIGHT;
+    for day in 0..days {
+        let label = (report.week_start + chrono::Duration::days(day)).format("%a %d").to_string();
+        let label_width = label.len() as f32 * SIZE * CHAR_WIDTH * PT_TO_MM;
+        let x = MARGIN + LABEL_WIDTH + day_width * (day as f32 + 0.5) - label_width / 2.0;
+        layout.text_at(&label, SIZE, false, MUTED, x, layout.y + 1.5);
+    }
+
+    for row in &rows {
+        layout.ensure(ROW_HEIGHT);
+        let top = layout.y;
+        layout.y -= ROW_HEIGHT;
+        for day in 0..=days {
+            let x = MARGIN + LABEL_WIDTH + day_width * day as f32;
+            layout.line((x, top), (x, layout.y));
+        }
+        let label = wrap(&row.service, SIZE, false, LABEL_WIDTH - 2.0).into_iter().next().unwrap_or_default();
+        layout.text_at(&label, SIZE, false, TEXT, MARGIN, layout.y + 1.5);
+        for bar in &row.bars {
+            let rgb = severity_color(&bar.severity);
+            let width = (plot_width * (bar.end - bar.start) as f32).max(0.6);
+            layout.rect(
+                MARGIN + LABEL_WIDTH + plot_width * bar.start as f32,
+                layout.y + 1.0,
+                width,
+                ROW_HEIGHT - 2.0,
+                if bar.estimated { lighten(rgb) } else { rgb },
+            );
+        }
+    }
+    layout.paragraph("UTC. Lighter bars have estimated times.", 7.5, false, MUTED, LABEL_WIDTH);
+    layout.gap(4.0);
+}
+
+/// Stacked bars of incidents by severity for the last twelve weeks, as in the SVG chart.
+fn push_severity_chart(layout: &mut Layout, report: &Report) {
+    const SIZE: f32 = 8.0;
+    const AXIS_WIDTH: f32 = 8.0;
+    const PLOT_HEIGHT: f32 = 40.0;
+
+    let (severities, weeks) = severity_history(report);
+    if weeks.is_empty() {
+        return;
+    }
+
+    layout.heading("Incidents by Severity", 13.0);
+    layout.ensure(PLOT_HEIGHT + 16.0);
+
+    // Legend
+    layout.y -= 4.0;
+    let mut x = MARGIN + AXIS_WIDTH;
+    for severity in &severities {
+        layout.rect(x, layout.y, 3.0, 3.0, severity_color(severity));
+        layout.text_at(severity, SIZE, false, TEXT, x + 4.0, layout.y + 0.3);
+        x += 8.0 + severity.len() as f32 * SIZE * CHAR_WIDTH * PT_TO_MM;
+    }
+    layout.y -= 4.0;
+
+    let most = weeks
+        .iter()
+        .map(|week| week.counts.iter().map(|(_, count)| count).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let step = most.div_ceil(4).max(1);
+    let top_value = step * most.div_ceil(step);
+    let scale = PLOT_HEIGHT / top_value as f32;
+    let bottom = layout.y - PLOT_HEIGHT;
+    for value in (0..=top_value).step_by(step) {
+        let y = bottom + value as f32 * scale;
+        layout.line((MARGIN + AXIS_WIDTH, y), (MARGIN + CONTENT_WIDTH, y));
+        layout.text_at(&value.to_string(), SIZE, false, MUTED, MARGIN, y - 1.0);
+    }
+
+    let slot = (CONTENT_WIDTH - AXIS_WIDTH) / SEVERITY_WEEKS as f32;
+    let first_slot = SEVERITY_WEEKS - weeks.len();
+    for (index, week) in weeks.iter().enumerate() {
+        let x = MARGIN + AXIS_WIDTH + slot * (first_slot + index) as f32 + slot * 0.2;
+        let mut y = bottom;
+        for (severity, count) in &week.counts {
+            let height = *count as f32 * scale;
+            layout.rect(x, y, slot * 0.6, height, severity_color(severity));
+            y += height;
+        }
+        layout.text_at(&week.label, SIZE, false, MUTED, x, bottom - 4.0);
+    }
+    layout.y = bottom - 6.0;
+    layout.gap(4.0);
+}
+
+fn action_line(record: &OutageRecord, action: &PreventativeAction) -> String {
+    let mut line = format!("- {}: {}", incident_label(record), action.key);
+    if let Some(summary) = &action.summary {
+        line.push_str(&format!(" {}", summary.trim()));
+    }
+    line.push_str(&action.progress());
+    line
+}
+
+fn push_actions(layout: &mut Layout, report: &Report) {
+    let actions = &report.actions;
+    if actions.is_empty() {
+        return;
+    }
+
+    layout.heading("Preventative Actions", 13.0);
+    if actions.incidents.is_empty() {
+        layout.paragraph("None for this week's incidents", 9.5, false, TEXT, 0.0);
+    }
+    for (record, incident_actions) in &actions.incidents {
+        if incident_actions.is_empty() {
+            let line = format!("- {}: no follow-up linked in JIRA", incident_label(record));
+            layout.paragraph(&line, 9.5, false, TEXT, 0.0);
+        }
+        for action in incident_actions {
+            layout.paragraph(&action_line(record, action), 9.5, false, TEXT, 0.0);
+        }
+    }
+    if !actions.carried_over.is_empty() {
+        layout.heading("Open Actions Carried Over from Previous Weeks", 11.0);
+        for (record, action) in &actions.carried_over {
+            layout.paragraph(&action_line(record, action), 9.5, false, TEXT, 0.0);
+        }
+    }
+    layout.gap(4.0);
+}
+
+fn push_incident(layout: &mut Layout, incident: &ReportIncident) {
+    const BADGE_WIDTH: f32 = 9.0;
+
+    layout.ensure(30.0);
+    layout.gap(3.0);
+    let severity = incident.record.severity.trim();
+    let height = line_height(11.0);
+    layout.rect(MARGIN, layout.y - height + 0.5, BADGE_WIDTH, height - 1.0, severity_color(severity));
+    layout.text_at(severity, 8.0, true, (0xff, 0xff, 0xff), MARGIN + 1.8, layout.y - height * 0.7);
+    // The header wraps beside the badge
+    let mut first = true;
+    for line in wrap(&incident.header(), 11.0, true, CONTENT_WIDTH - BADGE_WIDTH - 2.0) {
+        if !first {
+            layout.ensure(height);
+        }
+        first = false;
+        layout.y -= height;
+        layout.text_at(&line, 11.0, true, TEXT, MARGIN + BADGE_WIDTH + 2.0, layout.y + height * 0.25);
+    }
+
+    if let Some(key) = &incident.jira_key {
+        let ticket = match incident.ticket_url() {
+            Some(url) => format!("Ticket: {} ({})", key, url),
+            None => format!("Ticket: {}", key),
+        };
+        layout.paragraph(&ticket, 8.5, false, MUTED, 0.0);
+    }
+    if let Some(recurrence) = incident.recurrence {
+        layout.paragraph(&format!("Recurring: {}", recurrence), 9.0, true, RECURRING, 0.0);
+    }
+    layout.gap(1.0);
+    for paragraph in incident.body().split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        layout.paragraph(paragraph, 10.0, false, TEXT, 0.0);
+        layout.gap(1.5);
+    }
+}
+
+fn push_footers(layout: &Layout, title: &str) {
+    let count = layout.pages.len();
+    for (index, (page, layer_index)) in layout.pages.iter().enumerate() {
+        let layer = layout.doc.get_page(*page).get_layer(*layer_index);
+        let text = format!("{} · Page {} of {}", title, index + 1, count);
+        layer.set_fill_color(color(MUTED));
+        layer.use_text(pdf_text(&text), 8.0, Mm(MARGIN), Mm(FOOTER_Y), &layout.regular);
+    }
+}
+
+/// Renders the report as an A4 PDF for board packs: the header and logo, the KPI table,
+/// the charts and the actions, then the incidents from a new page.
+pub fn render_pdf(report: &Report) -> Result<Vec<u8>, Box<dyn Error>> {
+    let title = format!("{} – Week {}", report.title(), report.week_number);
+    let mut layout = Layout::new(&title)?;
+
+    let subtitle = format!(
+        "Week {} ({} - {}), all times UTC",
+        report.week_number,
+        report.week_start.format("%B %d"),
+        report.week_end.format("%B %d, %Y")
+    );
+    push_header(&mut layout, report.title(), &subtitle, report.ai.and_then(|ai| ai.summary.as_deref()));
+    push_metrics(&mut layout, &report.metrics, "This week");
+    push_timeline_chart(&mut layout, report);
+    push_downtime_chart(&mut layout, &downtime_by_service(report.incidents.iter().map(|incident| incident.record)));
+    push_severity_chart(&mut layout, report);
+    push_actions(&mut layout, report);
+
+    layout.new_page();
+    layout.heading("Incidents", 15.0);
+    if report.incidents.is_empty() {
+        layout.paragraph("No incidents this week.", 10.0, false, TEXT, 0.0);
+    }
+    for incident in &report.incidents {
+        push_incident(&mut layout, incident);
+    }
+
+    push_footers(&layout, &title);
+    Ok(layout.doc.save_to_bytes()?)
+}
+
+/// Writes the PDF report to `path`.
+pub fn write_pdf(report: &Report, path: &Path) -> Result<(), Box<dyn Error>> {
+    save_pdf(render_pdf(report)?, path)
+}
+
+/// Renders the monthly report as an A4 PDF: the KPIs against the previous month, a row per
+/// week, the downtime by service and a line per incident.
+pub fn render_monthly_pdf(report: &MonthlyReport) -> Result<Vec<u8>, Box<dyn Error>> {
+    let title = report.title();
+    let mut layout = Layout::new(&title)?;
+
+    push_header(&mut layout, &title, &format!("{}, all times UTC", report.period()), None);
+    push_metrics(&mut layout, &report.metrics, "This month");
+
+    let columns = [("Week", 50.0), ("Incidents", 50.0), ("Downtime", CONTENT_WIDTH - 100.0)];
+    let weeks = report
+        .weeks
+        .iter()
+        .map(|week| {
+            let severities: Vec<String> =
+                week.metrics.by_severity.iter().map(|(severity, count)| format!("{}: {}", severity, count)).collect();
+            let incidents = if severities.is_empty() {
+                week.metrics.incident_count.to_string()
+            } else {
+                format!("{} ({})", week.metrics.incident_count, severities.join(", "))
+            };
+            vec![week.label(), incidents, format_minutes(week.metrics.total_downtime_minutes as f64)]
+        })
+        .collect();
+    push_table(&mut layout, "Weekly Breakdown", &columns, weeks);
+    push_downtime_chart(&mut layout, &report.downtime);
+
+    layout.heading("Incidents", 13.0);
+    if report.outages.is_empty() {
+        layout.paragraph("No incidents this month.", 10.0, false, TEXT, 0.0);
+    }
+    for record in &report.outages {
+        layout.paragraph(&format!("- {}", incident_line(record)), 9.5, false, TEXT, 0.0);
+    }
+
+    push_footers(&layout, &title);
+    Ok(layout.doc.save_to_bytes()?)
+}
+
+/// Writes the monthly PDF report to `path`.
+pub fn write_monthly_pdf(report: &MonthlyReport, path: &Path) -> Result<(), Box<dyn Error>> {
+    save_pdf(render_monthly_pdf(report)?, path)
+}
+
+fn save_pdf(pdf: Vec<u8>, path: &Path) -> Result<(), Box<dyn Error>> {
+    fs::write(path, pdf).map_err(|e| format!("Failed to write the PDF report to {}: {}", path.display(), e))?;
+    info!("Wrote the PDF report to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monthly::month_range;
+    use crate::report::fixture::{outage, ReportFixture};
+    use std::collections::HashMap;
+
+    #[test]
+    fn renders_the_weekly_and_monthly_reports() {
+        let fixture = ReportFixture::new(vec![outage(1, "Nginx failed"), outage(2, "Disk filled up")]);
+        let pdf = render_pdf(&fixture.report()).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        let (start, end) = month_range(2026, 10).unwrap();
+        let monthly = MonthlyReport::build(&fixture.outages, &HashMap::new(), start, end);
+        let pdf = render_monthly_pdf(&monthly).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,240 @@
+use chrono::{Local, NaiveDate};
+use log::debug;
+use minijinja::Environment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::recurrence::Recurrence;
+use crate::structured::OutputMode;
+use crate::{calculate_incident_times, extract_jira_key, extract_rca_and_preventative_measures, parse_date, JiraIssue, OutageRecord};
+
+// Built-in copies of the templates in prompts/, used when PROMPT_DIR doesn't exist
+const BUILTIN_MANIFEST: &str = include_str!("../prompts/manifest.json");
+const BUILTIN_SYSTEM: &str = include_str!("../prompts/system.j2");
+const BUILTIN_INCIDENT: &str = include_str!("../prompts/incident.j2");
+const BUILTIN_SUMMARY: &str = include_str!("../prompts/summary.j2");
+const BUILTIN_EXAMPLES: &str = include_str!("../prompts/examples.txt");
+
+#[derive(Debug, Deserialize)]
+struct PromptManifest {
+    name: String,
+    version: String,
+    system: String,
+    incident: String,
+    summary: String,
+    examples: String,
+}
+
+/// A versioned set of prompt templates.
+#[derive(Debug, Clone)]
+pub struct PromptTemplates {
+    pub name: String,
+    pub version: String,
+    system: String,
+    incident: String,
+    summary: String,
+    examples: String,
+}
+
+/// Per-incident data made available to the templates as `outage` and `outages`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptOutage {
+    pub date: String,
+    pub service: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub duration: String,
+    pub severity: String,
+    pub cause: String,
+    pub solution: String,
+    pub jira_rca: String,
+    /// e.g. "3rd occurrence in 6 weeks (previously ...)" when the incident repeats an earlier one
+    pub recurrence: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct IncidentContext<'a> {
+    week_number: u32,
+    week_start: String,
+    week_end: String,
+    structured: bool,
+    outage: &'a PromptOutage,
+    examples: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct SummaryContext<'a> {
+    week_number: u32,
+    week_start: String,
+    week_end: String,
+    structured: bool,
+    outages: &'a [PromptOutage],
+    /// AI narrative for each outage, `None` where generating it failed
+    narratives: &'a [Option<String>],
+}
+
+/// The rendered system and user prompts.
+#[derive(Debug, Clone)]
+pub struct RenderedPrompt {
+    pub system: String,
+    pub user: String,
+}
+
+impl PromptTemplates {
+    /// Loads templates from `PROMPT_DIR` (default `prompts`), falling back to the built-in set.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let dir = env::var("PROMPT_DIR").unwrap_or_else(|_| "prompts".to_string());
+        let dir = Path::new(&dir);
+        let manifest_path = dir.join("manifest.json");
+
+        if !manifest_path.exists() {
+            debug!("No prompt manifest at {}, using built-in templates", manifest_path.display());
+            let manifest: PromptManifest = serde_json::from_str(BUILTIN_MANIFEST)?;
+            return Ok(PromptTemplates {
+                name: manifest.name,
+                version: manifest.version,
+                system: BUILTIN_SYSTEM.to_string(),
+                incident: BUILTIN_INCIDENT.to_string(),
+                summary: BUILTIN_SUMMARY.to_string(),
+                examples: BUILTIN_EXAMPLES.to_string(),
+            });
+        }
+
+        let read = |file: &str| {
+            fs::read_to_string(dir.join(file))
+                .map_err(|e| format!("Failed to read prompt template {}: {}", dir.join(file).display(), e))
+        };
+
+        let manifest: PromptManifest = serde_json::from_str(&read("manifest.json")?)
+            .map_err(|e| format!("Invalid prompt manifest {}: {}", manifest_path.display(), e))?;
+        debug!("Loaded prompt templates {} v{} from {}", manifest.name, manifest.version, dir.display());
+
+        Ok(PromptTemplates {
+            system: read(&manifest.system)?,
+            incident: read(&manifest.incident)?,
+            summary: read(&manifest.summary)?,
+            examples: read(&manifest.examples)?,
+            name: manifest.name,
+            version: manifest.version,
+        })
+    }
+
+    /// Identifies the template set, e.g. `weekly-report@1`.
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    fn render<S: Serialize>(&self, user_template: &str, context: &S) -> Result<RenderedPrompt, Box<dyn Error>> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.add_template("system", &self.system)?;
+        env.add_template("user", user_template)?;
+
+        Ok(RenderedPrompt {
+            system: env.get_template("system")?.render(context)?.trim_end().to_string(),
+            user: env.get_template("user")?.render(context)?,
+        })
+    }
+
+    /// Renders the prompt for a single incident's narrative.
+    pub fn render_incident(
+        &self,
+        mode: OutputMode,
+        outage: &PromptOutage,
+        week_number: u32,
+        week_start: &NaiveDate,
+        week_end: &NaiveDate,
+    ) -> Result<RenderedPrompt, Box<dyn Error>> {
+        let context = IncidentContext {
+            week_number,
+            week_start: week_start.format("%B %d").to_string(),
+            week_end: week_end.format("%B %d").to_string(),
+            structured: mode == OutputMode::Structured,
+            outage,
+            examples: self.examples.trim_end(),
+        };
+        self.render(&self.incident, &context)
+    }
+
+    /// Renders the prompt for the summary and recommendations pass.
+    pub fn render_summary(
+        &self,
+        mode: OutputMode,
+        outages: &[PromptOutage],
+        narratives: &[Option<String>],
+        week_number: u32,
+        week_start: &NaiveDate,
+        week_end: &NaiveDate,
+    ) -> Result<RenderedPrompt, Box<dyn Error>> {
+        let context = SummaryContext {
+            week_number,
+            week_start: week_start.format("%B %d").to_string(),
+            week_end: week_end.format("%B %d").to_string(),
+            structured: mode == OutputMode::Structured,
+            outages,
+            narratives,
+        };
+        self.render(&self.summary, &context)
+    }
+}
+
+/// Collects the CSV, JIRA and recurrence facts for each outage that the prompt templates need.
+pub fn prompt_outages(
+    outages: &[OutageRecord],
+    jira_details: &HashMap<String, JiraIssue>,
+    recurrences: &[Option<Recurrence>],
+) -> Vec<PromptOutage> {
+    let mut prompt_outages = Vec::new();
+
+    for (record, recurrence) in outages.iter().zip(recurrences) {
+        let jira_desc = if let Some(jira_key) = extract_jira_key(&record.ticket) {
+            debug!("Processing JIRA ticket: {}", jira_key);
+            if let Some(issue) = jira_details.get(&jira_key) {
+                if let Some(desc) = issue.fields.description.as_ref() {
+                    debug!("JIRA description found for {}, extracting RCA/PM", jira_key);
+                    extract_rca_and_preventative_measures(desc)
+                } else {
+                    debug!("No description field found for {}", jira_key);
+                    String::new()
+                }
+            } else {
+                debug!("JIRA details not found in cache for {}", jira_key);
+                String::new()
+            }
+        } else {
+            debug!("Could not extract JIRA key from ticket: {}", record.ticket);
+            String::new()
+        };
+
+        // Calculate start and end times
+        let incident_date = parse_date(&record.date).unwrap_or_else(|| Local::now().date_naive());
+        let full_jira_desc = if let Some(jira_key) = extract_jira_key(&record.ticket) {
+            jira_details.get(&jira_key)
+                .and_then(|issue| issue.fields.description.as_ref())
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let (start_time, end_time) = calculate_incident_times(&incident_date, &record.duration, &full_jira_desc);
+
+        prompt_outages.push(PromptOutage {
+            date: record.date.clone(),
+            service: record.service.clone(),
+            start_time,
+            end_time,
+            duration: record.duration.clone(),
+            severity: record.severity.clone(),
+            cause: record.cause.clone(),
+            solution: record.solution.clone(),
+            jira_rca: jira_desc,
+            recurrence: recurrence.as_ref().map(|r| r.to_string()),
+        });
+    }
+
+    prompt_outages
+}
@@ -0,0 +1,243 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+
+use crate::trends::TRAILING_WEEKS_LONG;
+use crate::{extract_jira_key, parse_date, JiraIssue, OutageRecord};
+
+/// How far back to look for earlier occurrences and how similar causes must be.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RecurrenceConfig {
+    pub weeks: usize,
+    /// Minimum token overlap (Jaccard index) between two cause texts
+    pub similarity: f64,
+}
+
+impl RecurrenceConfig {
+    /// Reads `RECURRENCE_WEEKS` (default 6, at most the 13 weeks of history loaded) and `RECURRENCE_SIMILARITY` (default 0.5).
+    pub fn from_env() -> Self {
+        let weeks = env::var("RECURRENCE_WEEKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(6)
+            .clamp(1, TRAILING_WEEKS_LONG);
+        let similarity = env::var("RECURRENCE_SIMILARITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.5);
+
+        RecurrenceConfig { weeks, similarity }
+    }
+}
+
+/// An incident that repeats earlier ones within the configured window.
+#[derive(Debug, Clone)]
+pub struct Recurrence {
+    /// Which occurrence this is, counting the incident itself
+    pub occurrence: usize,
+    pub weeks: usize,
+    pub previous: Vec<NaiveDate>,
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let previous: Vec<String> = self.previous.iter().map(|d| d.format("%B %d").to_string()).collect();
+        write!(
+            f,
+            "{} occurrence in {} weeks (previously {})",
+            ordinal(self.occurrence),
+            self.weeks,
+            previous.join(", ")
+        )
+    }
+}
+
+fn cause_tokens(cause: &str) -> HashSet<String> {
+    cause
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.len() > 2)
+        .map(str::to_string)
+        .collect()
+}
+
+fn cause_similarity(a: &str, b: &str) -> f64 {
+    let a = cause_tokens(a);
+    let b = cause_tokens(b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+}
+
+fn jira_components(record: &OutageRecord, jira_details: &HashMap<String, JiraIssue>) -> HashSet<String> {
+    extract_jira_key(&record.ticket)
+        .and_then(|key| jira_details.get(&key))
+        .map(|issue| issue.fields.components.iter().map(|c| c.name.to_lowercase()).collect())
+        .unwrap_or_default()
+}
+
+/// Two incidents are the same problem if they hit the same service and either their causes
+/// are similar or their JIRA issues share a component.
+fn is_repeat(
+    a: &OutageRecord,
+    b: &OutageRecord,
+    jira_details: &HashMap<String, JiraIssue>,
+    config: &RecurrenceConfig,
+) -> bool {
+    if !a.service.trim().eq_ignore_ascii_case(b.service.trim()) {
+        return false;
+    }
+    if cause_similarity(&a.cause, &b.cause) >= config.similarity {
+        return true;
+    }
+    let components = jira_components(a, jira_details);
+    !components.is_empty() && !components.is_disjoint(&jira_components(b, jira_details))
+}
+
+/// Finds earlier occurrences of each of this week's outages within `config.weeks` before it,
+/// looking at the history and at earlier outages in the same week.
+pub fn detect_recurrences(
+    outages: &[OutageRecord],
+    history_outages: &[OutageRecord],
+    jira_details: &HashMap<String, JiraIssue>,
+    config: &RecurrenceConfig,
+) -> Vec<Option<Recurrence>> {
+    let earlier: Vec<(Option<NaiveDate>, &OutageRecord)> = history_outages
+        .iter()
+        .chain(outages)
+        .map(|record| (parse_date(&record.date), record))
+        .collect();
+
+    outages
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let date = parse_date(&record.date)?;
+            let since = date - Duration::weeks(config.weeks as i64);
+            // Only outages listed before this one count, so two on the same day aren't each other's repeat
+            let position = history_outages.len() + index;
+            let mut previous: Vec<NaiveDate> = earlier
+                .iter()
+                .take(position)
+                .filter_map(|(other_date, other)| other_date.map(|other_date| (other_date, other)))
+                .filter(|(other_date, other)| {
+                    *other_date >= since && *other_date <= date && is_repeat(record, other, jira_details, config)
+                })
+                .map(|(other_date, _)| other_date)
+                .collect();
+            if previous.is_empty() {
+                return None;
+            }
+            previous.sort();
+
+            Some(Recurrence {
+                occurrence: previous.len() + 1,
+                weeks: config.weeks,
+                previous,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::outage;
+
+    const CONFIG: RecurrenceConfig = RecurrenceConfig { weeks: 6, similarity: 0.5 };
+
+    fn on(ticket: usize, date: &str, cause: &str) -> OutageRecord {
+        OutageRecord {
+            date: date.to_string(),
+            ..outage(ticket, cause)
+        }
+    }
+
+    fn with_components(ticket: usize, components: &[&str]) -> (String, JiraIssue) {
+        let key = format!("OPS-{}", ticket);
+        let components: Vec<_> = components.iter().map(|name| serde_json::json!({ "name": name })).collect();
+        let payload = serde_json::json!({ "key": key, "fields": { "components": components } }).to_string();
+        (key, JiraIssue::from_payload(&payload).unwrap())
+    }
+
+    #[test]
+    fn similar_causes_repeat_at_the_threshold() {
+        let no_jira = HashMap::new();
+        let nginx = on(1, "12/Oct/26", "Nginx worker crashed on web");
+        // 3 of 6 distinct tokens shared, exactly the threshold
+        let similar = on(2, "05/Oct/26", "NGINX worker crashed, api node");
+        assert!(is_repeat(&nginx, &similar, &no_jira, &CONFIG));
+        assert!(!is_repeat(&nginx, &similar, &no_jira, &RecurrenceConfig { similarity: 0.51, ..CONFIG }));
+
+        assert!(!is_repeat(&nginx, &on(3, "05/Oct/26", "Database disk full"), &no_jira, &CONFIG));
+        // Same cause on a different service is a different problem
+        let market = OutageRecord { service: "Market".to_string(), ..similar.clone() };
+        assert!(!is_repeat(&nginx, &market, &no_jira, &CONFIG));
+        // Short words and punctuation don't count towards similarity
+        assert!(!is_repeat(&on(4, "12/Oct/26", "It is a DB"), &on(5, "05/Oct/26", "it is, a db!"), &no_jira, &CONFIG));
+    }
+
+    #[test]
+    fn shared_jira_components_repeat_despite_different_causes() {
+        let first = on(1, "12/Oct/26", "Nginx worker crashed");
+        let second = on(2, "05/Oct/26", "Database disk full");
+        let third = on(3, "05/Oct/26", "Certificate expired");
+        let jira_details = HashMap::from([
+            with_components(1, &["Web", "Load Balancer"]),
+            with_components(2, &["load balancer"]),
+            with_components(3, &["Database"]),
+        ]);
+
+        assert!(is_repeat(&first, &second, &jira_details, &CONFIG));
+        assert!(!is_repeat(&first, &third, &jira_details, &CONFIG));
+        assert!(!is_repeat(&first, &second, &HashMap::new(), &CONFIG));
+    }
+
+    #[test]
+    fn counts_earlier_occurrences_within_the_window() {
+        let cause = "Nginx worker crashed";
+        let history = vec![
+            on(1, "24/Aug/26", cause),
+            on(2, "31/Aug/26", cause),
+            on(3, "28/Sep/26", cause),
+            on(4, "29/Sep/26", "Database disk full"),
+        ];
+        let outages = vec![on(5, "12/Oct/26", cause), on(6, "12/Oct/26", cause), on(7, "not a date", cause)];
+        let recurrences = detect_recurrences(&outages, &history, &HashMap::new(), &CONFIG);
+
+        // August 24 is more than 6 weeks before October 12, August 31 is exactly 6
+        let first = recurrences[0].as_ref().unwrap();
+        assert_eq!(first.occurrence, 3);
+        assert_eq!(first.to_string(), "3rd occurrence in 6 weeks (previously August 31, September 28)");
+        // The second outage that day repeats the first, but not the other way round
+        assert_eq!(recurrences[1].as_ref().unwrap().occurrence, 4);
+        assert!(recurrences[2].is_none());
+
+        let alone = detect_recurrences(&outages[..1], &[], &HashMap::new(), &CONFIG);
+        assert!(alone[0].is_none());
+    }
+
+    #[test]
+    fn ordinals() {
+        let numbers = [1, 2, 3, 4, 11, 12, 13, 21, 22, 23, 101, 111, 112];
+        let ordinals: Vec<String> = numbers.into_iter().map(ordinal).collect();
+        assert_eq!(
+            ordinals,
+            vec!["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "23rd", "101st", "111th", "112th"]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+use crate::actions::ActionsReport;
+use crate::ai_report::AiReport;
+use crate::metrics::{MetricRow, WeeklyMetrics};
+use crate::prompts::PromptOutage;
+use crate::trends::TrendComparison;
+use crate::{
+    calculate_incident_times, extract_jira_key, extract_rca_sections, extract_time_from_description, format_description,
+    format_outage_entry, format_outage_header, parse_date, JiraIssue, OutageRecord, RcaSections,
+};
+
+/// When an incident started and ended, in UTC.
+#[derive(Debug, Clone, Copy)]
+pub struct IncidentWindow {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    /// Whether the times are estimated from the duration rather than stated in JIRA
+    pub estimated: bool,
+}
+
+/// Works out the incident's start and end from its JIRA description, or estimates them
+/// from the duration the same way the AI prompts do.
+fn incident_window(record: &OutageRecord, description: &str) -> Option<IncidentWindow> {
+    let date = parse_date(&record.date)?;
+    let estimated = extract_time_from_description(description).0.is_none();
+    let (start, end) = calculate_incident_times(&date, &record.duration, description);
+    let start = NaiveDateTime::new(date, NaiveTime::parse_from_str(&start, "%H:%M").ok()?);
+    let mut end = NaiveDateTime::new(date, NaiveTime::parse_from_str(&end, "%H:%M").ok()?);
+    if end < start {
+        // Ended after midnight
+        end += Duration::days(1);
+    }
+    Some(IncidentWindow { start, end, estimated })
+}
+
+/// One incident as every output format presents it.
+pub struct ReportIncident<'a> {
+    pub record: &'a OutageRecord,
+    pub jira_key: Option<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub recurrence: Option<&'a str>,
+    /// The AI narrative, if one was generated for this incident
+    pub narrative: Option<&'a str>,
+    pub window: Option<IncidentWindow>,
+    /// RCA and preventative measures from the JIRA description
+    pub rca: RcaSections,
+}
+
+impl ReportIncident<'_> {
+    pub fn header(&self) -> String {
+        format_outage_header(self.record, self.start_time.clone(), self.end_time.clone())
+    }
+
+    /// The AI narrative, or the CSV cause and solution.
+    pub fn body(&self) -> String {
+        match self.narrative {
+            Some(narrative) => narrative.to_string(),
+            None => format_description(&self.record.cause, &self.record.solution),
+        }
+    }
+
+    /// Link to the incident's ticket, if the `Ticket` column holds a URL.
+    pub fn ticket_url(&self) -> Option<&str> {
+        let ticket = self.record.ticket.trim();
+        ticket.starts_with("http").then_some(ticket)
+    }
+}
+
+/// Everything a report shows, independent of the output format.
+pub struct Report<'a> {
+    pub week_number: u32,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    pub metrics: Vec<MetricRow>,
+    /// The numbers behind `metrics`
+    pub kpis: &'a WeeklyMetrics,
+    pub trends: &'a TrendComparison,
+    pub actions: ActionsReport<'a>,
+    pub incidents: Vec<ReportIncident<'a>>,
+    pub ai: Option<&'a AiReport>,
+}
+
+impl Report<'_> {
+    pub fn title(&self) -> &'static str {
+        if self.ai.is_some() {
+            "Weekly Stability Report (AI-Generated)"
+        } else {
+            "Weekly Stability Report"
+        }
+    }
+
+    /// File name stem for this week's outputs, e.g. `week-2026-41`.
+    pub fn file_stem(&self) -> String {
+        format!("week-{}-{:02}", self.week_start.format("%G"), self.week_number)
+    }
+
+    /// Entries in the standard plain-text format.
+    pub fn standard_entries(&self) -> Vec<String> {
+        self.incidents
+            .iter()
+            .map(|incident| {
+                format_outage_entry(
+                    incident.record,
+                    incident.start_time.clone(),
+                    incident.end_time.clone(),
+                    incident.recurrence,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Builds the report's incidents. The AI report shows estimated times for every incident,
+/// the standard format only times the JIRA description states.
+pub fn report_incidents<'a>(
+    outages: &'a [OutageRecord],
+    prompt_outages: &'a [PromptOutage],
+    jira_details: &HashMap<String, JiraIssue>,
+    ai: Option<&'a AiReport>,
+) -> Vec<ReportIncident<'a>> {
+    outages
+        .iter()
+        .zip(prompt_outages)
+        .enumerate()
+        .map(|(index, (record, prompt_outage))| {
+            let jira_key = extract_jira_key(&record.ticket);
+            let description = jira_key
+                .as_ref()
+                .and_then(|key| jira_details.get(key))
+                .and_then(|issue| issue.fields.description.as_deref());
+            let (start_time, end_time) = match ai {
+                Some(_) => (Some(prompt_outage.start_time.clone()), Some(prompt_outage.end_time.clone())),
+                None => description.map(extract_time_from_description).unwrap_or((None, None)),
+            };
+            let narrative = ai
+                .and_then(|ai| ai.narratives.get(index))
+                .and_then(|narrative| narrative.as_deref());
+
+            ReportIncident {
+                record,
+                jira_key,
+                start_time,
+                end_time,
+                recurrence: prompt_outage.recurrence.as_deref(),
+                narrative,
+                window: incident_window(record, description.unwrap_or_default()),
+                rca: description.map(extract_rca_sections).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// Browse URL for another issue on the same JIRA site as `ticket`.
+pub fn issue_url(ticket: &str, key: &str) -> Option<String> {
+    let (base, _) = ticket.trim().rsplit_once("/browse/")?;
+    Some(format!("{}/browse/{}", base, key))
+}
+
+/// Where dry runs write outgoing messages, `OUTBOX_DIR` (default `outbox`).
+pub fn outbox_dir() -> PathBuf {
+    PathBuf::from(env::var("OUTBOX_DIR").unwrap_or_else(|_| "outbox".to_string()))
+}
+
+/// Builds reports from synthetic outages for the channel tests.
+#[cfg(test)]
+pub mod fixture {
+    use super::*;
+    use crate::actions::collect_actions;
+    use crate::metrics::{compute_weekly_metrics, metric_rows};
+    use crate::prompts::prompt_outages;
+
+    /// Owns everything a `Report` borrows.
+    pub struct ReportFixture {
+        pub outages: Vec<OutageRecord>,
+        pub prompt_outages: Vec<PromptOutage>,
+        pub jira_details: HashMap<String, JiraIssue>,
+        pub metrics: WeeklyMetrics,
+        pub trends: TrendComparison,
+    }
+
+    /// An S2 outage in week 41 of 2026 whose cause and solution are `text`.
+    pub fn outage(ticket: usize, text: &str) -> OutageRecord {
+        OutageRecord {
+            date: "12/Oct/26".to_string(),
+            ticket: format!("https://example.atlassian.net/browse/OPS-{}", ticket),
+            service: "Sales-I US".to_string(),
+            duration: "15".to_string(),
+            cause: text.to_string(),
+            solution: text.to_string(),
+            severity: "S2".to_string(),
+            detected: None,
+        }
+    }
+
+    impl ReportFixture {
+        pub fn new(outages: Vec<OutageRecord>) -> Self {
+            let jira_details = HashMap::new();
+            let recurrences = vec![None; outages.len()];
+            ReportFixture {
+                prompt_outages: prompt_outages(&outages, &jira_details, &recurrences),
+                metrics: compute_weekly_metrics(&outages, &jira_details),
+                trends: TrendComparison::default(),
+                outages,
+                jira_details,
+            }
+        }
+
+        pub fn report(&self) -> Report<'_> {
+            Report {
+                week_number: 41,
+                week_start: NaiveDate::from_ymd_opt(2026, 10, 11).unwrap(),
+                week_end: NaiveDate::from_ymd_opt(2026, 10, 17).unwrap(),
+                metrics: metric_rows(&self.metrics, Some(&self.trends)),
+                kpis: &self.metrics,
+                trends: &self.trends,
+                actions: collect_actions(&self.outages, &[], &self.jira_details),
+                incidents: report_incidents(&self.outages, &self.prompt_outages, &self.jira_details, None),
+                ai: None,
+            }
+        }
+    }
+}
@@ -0,0 +1,672 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ai_report::{incident_narrative, report_summary};
+use crate::editor::edit_text;
+use crate::format_outage_header;
+use crate::snapshot::Snapshot;
+
+/// Unchanged lines shown around each change in a diff.
+const DIFF_CONTEXT: usize = 2;
+/// Starts the headings and instructions in a draft opened for editing. Narratives never start with it,
+/// unlike Markdown headings or channel names.
+const MARKER: &str = "<<<";
+
+/// Where a week's report is in review. Only an approved report can be delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewState {
+    Draft,
+    InReview,
+    Approved,
+}
+
+impl fmt::Display for ReviewState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReviewState::Draft => "open for changes",
+            ReviewState::InReview => "in review",
+            ReviewState::Approved => "approved",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewAction {
+    /// Written by a report run
+    Generated,
+    /// Changed by hand with `review edit`
+    Edited,
+    Submitted,
+    Approved,
+    /// Sent back from review for changes
+    Rejected,
+    Delivered,
+}
+
+impl fmt::Display for ReviewAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReviewAction::Generated => "generated",
+            ReviewAction::Edited => "edited",
+            ReviewAction::Submitted => "submitted for review",
+            ReviewAction::Approved => "approved",
+            ReviewAction::Rejected => "sent back",
+            ReviewAction::Delivered => "delivered",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewEvent {
+    pub at: DateTime<Utc>,
+    pub by: String,
+    pub revision: u32,
+    pub action: ReviewAction,
+    /// Why a draft was sent back, or where it was delivered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl fmt::Display for ReviewEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}  draft {} {} by {}",
+            self.at.format("%Y-%m-%d %H:%M UTC"),
+            self.revision,
+            self.action,
+            self.by
+        )?;
+        match &self.note {
+            Some(note) => write!(f, ": {}", note),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The review state of a week's latest draft, and everything that happened to its drafts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewStatus {
+    pub state: ReviewState,
+    /// The latest draft, which `state` applies to
+    pub revision: u32,
+    /// Where the latest draft has been delivered since it was approved
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delivered: Vec<String>,
+    pub history: Vec<ReviewEvent>,
+}
+
+/// Who is acting on a draft, from `USER` or `USERNAME`.
+fn reviewer() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// A week's report drafts, kept as numbered snapshots in `DRAFTS_DIR/week-<year>-<week>`
+/// next to a `review.json` with their review state.
+pub struct DraftStore {
+    dir: PathBuf,
+    week: String,
+}
+
+impl DraftStore {
+    /// Drafts for `week`, written as `<year>-<week>`, e.g. `2026-41`.
+    pub fn new(week: &str) -> Self {
+        let dir = env::var("DRAFTS_DIR").unwrap_or_else(|_| "drafts".to_string());
+        Self::in_dir(Path::new(&dir), week)
+    }
+
+    fn in_dir(dir: &Path, week: &str) -> Self {
+        DraftStore {
+            dir: dir.join(format!("week-{}", week)),
+            week: week.to_string(),
+        }
+    }
+
+    pub fn for_week(week_start: &NaiveDate, week_number: u32) -> Self {
+        Self::new(&format!("{}-{:02}", week_start.format("%G"), week_number))
+    }
+
+    pub fn week(&self) -> &str {
+        &self.week
+    }
+
+    fn status_path(&self) -> PathBuf {
+        self.dir.join("review.json")
+    }
+
+    fn draft_path(&self, revision: u32) -> PathBuf {
+        self.dir.join(format!("draft-{}.json", revision))
+    }
+
+    /// The review status, `None` if no draft has been saved for the week.
+    pub fn status(&self) -> Result<Option<ReviewStatus>, Box<dyn Error>> {
+        let path = self.status_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    pub fn current(&self) -> Result<ReviewStatus, Box<dyn Error>> {
+        self.status()?
+            .ok_or_else(|| format!("No draft has been saved for week {}", self.week).into())
+    }
+
+    fn save_status(&self, status: &ReviewStatus) -> Result<(), Box<dyn Error>> {
+        fs::write(self.status_path(), serde_json::to_string_pretty(status)?)?;
+        Ok(())
+    }
+
+    pub fn load(&self, revision: u32) -> Result<Snapshot, Box<dyn Error>> {
+        Snapshot::load(&self.draft_path(revision))
+            .map_err(|e| format!("Could not load draft {} of week {}: {}", revision, self.week, e).into())
+    }
+
+    /// The review status and the latest draft.
+    pub fn latest(&self) -> Result<(ReviewStatus, Snapshot), Box<dyn Error>> {
+        let status = self.current()?;
+        let snapshot = self.load(status.revision)?;
+        Ok((status, snapshot))
+    }
+
+    /// Saves the snapshot as the week's next draft, which has to be reviewed again, and returns
+    /// its revision. Returns `None` if its report is the same as the latest draft's.
+    pub fn save_draft(&self, snapshot: &Snapshot, action: ReviewAction) -> Result<Option<u32>, Box<dyn Error>> {
+        let status = self.status()?;
+        if let Some(status) = &status {
+            if self.load(status.revision)?.output == snapshot.output {
+                return Ok(None);
+            }
+        }
+
+        let mut status = status.unwrap_or(ReviewStatus {
+            state: ReviewState::Draft,
+            revision: 0,
+            delivered: Vec::new(),
+            history: Vec::new(),
+        });
+        status.revision += 1;
+        status.state = ReviewState::Draft;
+        status.delivered.clear();
+        status.history.push(ReviewEvent {
+            at: Utc::now(),
+            by: reviewer(),
+            revision: status.revision,
+            action,
+            note: None,
+        });
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.draft_path(status.revision), serde_json::to_string_pretty(snapshot)?)?;
+        self.save_status(&status)?;
+        Ok(Some(status.revision))
+    }
+
+    /// Moves the latest draft through review: a draft is submitted, then approved or sent back,
+    /// and only an approved draft is delivered.
+    pub fn record(&self, action: ReviewAction, note: Option<String>) -> Result<ReviewStatus, Box<dyn Error>> {
+        let mut status = self.current()?;
+        self.apply(&mut status, action, note)?;
+        self.save_status(&status)?;
+        Ok(status)
+    }
+
+    /// Records that the approved draft was delivered to `destinations`.
+    pub fn record_delivery(&self, destinations: &[&str]) -> Result<ReviewStatus, Box<dyn Error>> {
+        let mut status = self.current()?;
+        self.apply(&mut status, ReviewAction::Delivered, Some(destinations.join(", ")))?;
+        for destination in destinations {
+            if !status.delivered.iter().any(|delivered| delivered == destination) {
+                status.delivered.push(destination.to_string());
+            }
+        }
+        self.save_status(&status)?;
+        Ok(status)
+    }
+
+    fn apply(&self, status: &mut ReviewStatus, action: ReviewAction, note: Option<String>) -> Result<(), Box<dyn Error>> {
+        status.state = match (action, status.state) {
+            (ReviewAction::Submitted, ReviewState::Draft) => ReviewState::InReview,
+            (ReviewAction::Approved, ReviewState::InReview) => ReviewState::Approved,
+            (ReviewAction::Rejected, ReviewState::InReview) => ReviewState::Draft,
+            (ReviewAction::Delivered, ReviewState::Approved) => ReviewState::Approved,
+            (action, state) => {
+                return Err(format!(
+                    "Draft {} of week {} can't be {}, it is {}",
+                    status.revision, self.week, action, state
+                )
+                .into())
+            }
+        };
+        status.history.push(ReviewEvent {
+            at: Utc::now(),
+            by: reviewer(),
+            revision: status.revision,
+            action,
+            note,
+        });
+        Ok(())
+    }
+}
+
+/// A piece of the draft that can be edited by hand. AI narratives are edited as written,
+/// incidents in the standard format through their CSV cause and solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DraftText {
+    Summary,
+    Narrative(usize),
+    Cause(usize),
+    Solution(usize),
+}
+
+impl DraftText {
+    fn label(self) -> String {
+        match self {
+            DraftText::Summary => "Summary".to_string(),
+            DraftText::Narrative(index) => format!("Incident {} narrative", index + 1),
+            DraftText::Cause(index) => format!("Incident {} cause", index + 1),
+            DraftText::Solution(index) => format!("Incident {} solution", index + 1),
+        }
+    }
+
+    fn incident(self) -> Option<usize> {
+        match self {
+            DraftText::Summary => None,
+            DraftText::Narrative(index) | DraftText::Cause(index) | DraftText::Solution(index) => Some(index),
+        }
+    }
+}
+
+/// The draft's editable texts as the report currently shows them, in report order.
+fn draft_texts(snapshot: &Snapshot) -> Vec<(DraftText, String)> {
+    let mut texts = Vec::new();
+    if let Some(summary) = snapshot.llm.as_ref().and_then(|llm| report_summary(llm).ok()) {
+        texts.push((DraftText::Summary, summary));
+    }
+    for (index, record) in snapshot.outages.iter().enumerate() {
+        match snapshot.llm.as_ref().and_then(|llm| incident_narrative(llm, index).ok()) {
+            Some(narrative) => texts.push((DraftText::Narrative(index), narrative)),
+            None => {
+                texts.push((DraftText::Cause(index), record.cause.clone()));
+                texts.push((DraftText::Solution(index), record.solution.clone()));
+            }
+        }
+    }
+    texts
+}
+
+fn draft_document(snapshot: &Snapshot, revision: u32, texts: &[(DraftText, String)]) -> String {
+    let mut document = format!(
+        "{m}# Week {} ({} - {}), draft {}\n\
+         {m}# Edit the text under each {m} heading, then save and close the editor. Keep the headings\n\
+         {m}# as they are. Lines starting with {m}# are ignored, all other lines are kept as written.\n",
+        snapshot.week_number,
+        snapshot.week_start.format("%B %d"),
+        snapshot.week_end.format("%B %d"),
+        revision,
+        m = MARKER
+    );
+    for (text, value) in texts {
+        let heading = match text.incident().and_then(|index| snapshot.outages.get(index)) {
+            Some(record) => format!("{}: {}", text.label(), format_outage_header(record, None, None)),
+            None => text.label(),
+        };
+        document.push_str(&format!("\n{} {}\n{}\n", MARKER, heading, value.trim()));
+    }
+    document
+}
+
+/// Reads the texts back from an edited document. Headings that were removed leave their text unchanged.
+fn parse_document(document: &str, texts: &[(DraftText, String)]) -> Result<Vec<(DraftText, String)>, Box<dyn Error>> {
+    let mut parsed: Vec<(DraftText, Vec<&str>)> = Vec::new();
+    for line in document.lines() {
+        if let Some(marker) = line.strip_prefix(MARKER) {
+            if marker.starts_with('#') {
+                continue;
+            }
+            let label = marker.split(':').next().unwrap_or_default().trim();
+            let text = texts
+                .iter()
+                .map(|(text, _)| *text)
+                .find(|text| text.label() == label)
+                .ok_or_else(|| format!("Unknown heading \"{}\" in the edited draft", marker.trim()))?;
+            parsed.push((text, Vec::new()));
+        } else if let Some((_, lines)) = parsed.last_mut() {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            return Err(format!("Text before the first heading in the edited draft: {}", line.trim()).into());
+        }
+    }
+    Ok(parsed
+        .into_iter()
+        .map(|(text, lines)| (text, lines.join("\n").trim().to_string()))
+        .collect())
+}
+
+/// Opens the draft's summary and incident texts in the user's editor and applies the changes
+/// to `snapshot`. Returns whether anything changed.
+pub fn edit_draft(snapshot: &mut Snapshot, revision: u32) -> Result<bool, Box<dyn Error>> {
+    let texts = draft_texts(snapshot);
+    let document = edit_text(&draft_document(snapshot, revision, &texts), "draft.md")?;
+
+    let mut changed = false;
+    for (text, value) in parse_document(&document, &texts)? {
+        let current = texts.iter().find(|(current, _)| *current == text).map(|(_, value)| value.trim());
+        if current == Some(value.as_str()) {
+            continue;
+        }
+        if value.is_empty() && matches!(text, DraftText::Summary | DraftText::Narrative(_)) {
+            return Err(format!("{} can't be empty", text.label()).into());
+        }
+        changed = true;
+
+        let llm = snapshot.llm.as_mut();
+        match text {
+            DraftText::Summary => {
+                if let Some(exchange) = llm.and_then(|llm| llm.summary.as_mut()) {
+                    exchange.edited = Some(value);
+                }
+            }
+            DraftText::Narrative(index) => {
+                if let Some(exchange) = llm.and_then(|llm| llm.incidents.get_mut(index)) {
+                    exchange.edited = Some(value);
+                }
+            }
+            DraftText::Cause(index) => snapshot.outages[index].cause = value,
+            DraftText::Solution(index) => snapshot.outages[index].solution = value,
+        }
+    }
+    Ok(changed)
+}
+
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff from the longest common subsequence of the two texts.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    lines
+}
+
+/// Unified-style diff of two report texts, empty if they are the same.
+pub fn render_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let lines = diff_lines(&old_lines, &new_lines);
+
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut text = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut last_shown: Option<usize> = None;
+    let mut new_line = 1;
+    for (index, line) in lines.iter().enumerate() {
+        if changed.iter().any(|&change| change.abs_diff(index) <= DIFF_CONTEXT) {
+            if last_shown.is_none_or(|last| last + 1 != index) {
+                text.push_str(&format!("@@ line {} @@\n", new_line));
+            }
+            let (prefix, content) = match line {
+                DiffLine::Same(content) => (' ', content),
+                DiffLine::Removed(content) => ('-', content),
+                DiffLine::Added(content) => ('+', content),
+            };
+            text.push_str(&format!("{}{}\n", prefix, content));
+            last_shown = Some(index);
+        }
+        if !matches!(line, DiffLine::Removed(_)) {
+            new_line += 1;
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::outage;
+    use std::collections::BTreeMap;
+
+    /// Drafts in a directory of their own, removed when the test is done.
+    struct TestDrafts(DraftStore);
+
+    impl TestDrafts {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!("weekly-status-report-drafts-{}-{}", std::process::id(), name));
+            TestDrafts(DraftStore::in_dir(&dir, "2026-41"))
+        }
+
+        fn with_state(name: &str, state: ReviewState) -> Self {
+            let drafts = Self::new(name);
+            drafts.0.save_draft(&snapshot("report"), ReviewAction::Generated).unwrap();
+            let mut status = drafts.0.current().unwrap();
+            status.state = state;
+            drafts.0.save_status(&status).unwrap();
+            drafts
+        }
+    }
+
+    impl Drop for TestDrafts {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0.dir);
+        }
+    }
+
+    fn snapshot(output: &str) -> Snapshot {
+        Snapshot {
+            version: crate::snapshot::SNAPSHOT_VERSION,
+            created_at: Utc::now(),
+            week_number: 41,
+            week_start: NaiveDate::from_ymd_opt(2026, 10, 11).unwrap(),
+            week_end: NaiveDate::from_ymd_opt(2026, 10, 17).unwrap(),
+            data_since: None,
+            outages: vec![outage(1001, "Firewall restarted")],
+            history_outages: Vec::new(),
+            recurrence: None,
+            jira_payloads: BTreeMap::new(),
+            llm: None,
+            output: output.to_string(),
+        }
+    }
+
+    fn actions(status: &ReviewStatus) -> Vec<(u32, ReviewAction)> {
+        status.history.iter().map(|event| (event.revision, event.action)).collect()
+    }
+
+    #[test]
+    fn allowed_transitions() {
+        let allowed = [
+            (ReviewState::Draft, ReviewAction::Submitted, ReviewState::InReview),
+            (ReviewState::InReview, ReviewAction::Approved, ReviewState::Approved),
+            (ReviewState::InReview, ReviewAction::Rejected, ReviewState::Draft),
+            (ReviewState::Approved, ReviewAction::Delivered, ReviewState::Approved),
+        ];
+        for (index, (from, action, to)) in allowed.into_iter().enumerate() {
+            let drafts = TestDrafts::with_state(&format!("allowed-{}", index), from);
+            let status = drafts.0.record(action, Some("note".to_string())).unwrap();
+            assert_eq!(status.state, to, "{:?} from {:?}", action, from);
+            assert_eq!(drafts.0.current().unwrap().state, to);
+            let event = status.history.last().unwrap();
+            assert_eq!((event.revision, event.action, event.note.as_deref()), (1, action, Some("note")));
+        }
+    }
+
+    #[test]
+    fn refused_transitions_leave_the_draft_unchanged() {
+        let states = [ReviewState::Draft, ReviewState::InReview, ReviewState::Approved];
+        let actions = [ReviewAction::Submitted, ReviewAction::Approved, ReviewAction::Rejected, ReviewAction::Delivered];
+        let allowed = [
+            (ReviewState::Draft, ReviewAction::Submitted),
+            (ReviewState::InReview, ReviewAction::Approved),
+            (ReviewState::InReview, ReviewAction::Rejected),
+            (ReviewState::Approved, ReviewAction::Delivered),
+        ];
+        for (index, (state, action)) in states
+            .iter()
+            .flat_map(|state| actions.iter().map(move |action| (*state, *action)))
+            .filter(|pair| !allowed.contains(pair))
+            .enumerate()
+        {
+            let drafts = TestDrafts::with_state(&format!("refused-{}", index), state);
+            let error = drafts.0.record(action, None).unwrap_err();
+            assert_eq!(error.to_string(), format!("Draft 1 of week 2026-41 can't be {}, it is {}", action, state));
+            let status = drafts.0.current().unwrap();
+            assert_eq!((status.state, status.history.len()), (state, 1));
+        }
+
+        // Generated and edited drafts are saved, not recorded
+        let drafts = TestDrafts::with_state("refused-saves", ReviewState::Draft);
+        assert!(drafts.0.record(ReviewAction::Generated, None).is_err());
+        assert!(drafts.0.record(ReviewAction::Edited, None).is_err());
+    }
+
+    #[test]
+    fn nothing_to_record_without_a_draft() {
+        let drafts = TestDrafts::new("missing");
+        let error = drafts.0.record(ReviewAction::Submitted, None).unwrap_err();
+        assert_eq!(error.to_string(), "No draft has been saved for week 2026-41");
+    }
+
+    #[test]
+    fn deliveries_are_only_recorded_for_approved_drafts() {
+        let drafts = TestDrafts::with_state("delivery-refused", ReviewState::InReview);
+        assert!(drafts.0.record_delivery(&["email"]).is_err());
+        assert!(drafts.0.current().unwrap().delivered.is_empty());
+
+        let drafts = TestDrafts::with_state("delivery", ReviewState::Approved);
+        drafts.0.record_delivery(&["email"]).unwrap();
+        let status = drafts.0.record_delivery(&["email", "Slack"]).unwrap();
+        assert_eq!(status.state, ReviewState::Approved);
+        assert_eq!(status.delivered, vec!["email", "Slack"]);
+        assert_eq!(status.history.last().unwrap().note.as_deref(), Some("email, Slack"));
+    }
+
+    #[test]
+    fn saving_the_same_report_keeps_the_draft() {
+        let drafts = TestDrafts::new("dedupe");
+        assert_eq!(drafts.0.save_draft(&snapshot("report"), ReviewAction::Generated).unwrap(), Some(1));
+        assert_eq!(drafts.0.save_draft(&snapshot("report"), ReviewAction::Generated).unwrap(), None);
+        assert_eq!(drafts.0.save_draft(&snapshot("report"), ReviewAction::Edited).unwrap(), None);
+
+        let status = drafts.0.current().unwrap();
+        assert_eq!((status.revision, status.state), (1, ReviewState::Draft));
+        assert_eq!(actions(&status), vec![(1, ReviewAction::Generated)]);
+    }
+
+    #[test]
+    fn a_changed_report_is_a_new_draft_to_review_again() {
+        let drafts = TestDrafts::new("reset");
+        drafts.0.save_draft(&snapshot("report"), ReviewAction::Generated).unwrap();
+        drafts.0.record(ReviewAction::Submitted, None).unwrap();
+        drafts.0.record(ReviewAction::Approved, None).unwrap();
+        drafts.0.record_delivery(&["email"]).unwrap();
+
+        assert_eq!(drafts.0.save_draft(&snapshot("changed report"), ReviewAction::Edited).unwrap(), Some(2));
+        let (status, latest) = drafts.0.latest().unwrap();
+        assert_eq!((status.revision, status.state), (2, ReviewState::Draft));
+        assert!(status.delivered.is_empty());
+        assert_eq!(latest.output, "changed report");
+        assert_eq!(drafts.0.load(1).unwrap().output, "report");
+        assert_eq!(
+            actions(&status),
+            vec![
+                (1, ReviewAction::Generated),
+                (1, ReviewAction::Submitted),
+                (1, ReviewAction::Approved),
+                (1, ReviewAction::Delivered),
+                (2, ReviewAction::Edited),
+            ]
+        );
+    }
+
+    #[test]
+    fn edited_document_round_trips_every_line_but_markers() {
+        let snapshot = snapshot("report");
+        let texts = vec![
+            (DraftText::Summary, "A quiet week.\n\n# Not a comment\n#ops-weekly was told.".to_string()),
+            (DraftText::Narrative(0), "## Impact\nSales-I US was down.\n\n## Cause\nA firewall restart.".to_string()),
+        ];
+        let document = draft_document(&snapshot, 3, &texts);
+        assert!(document.starts_with("<<<# Week 41 (October 11 - October 17), draft 3\n"));
+        assert!(document.contains("\n<<< Incident 1 narrative: October 12 (15min) Sales-I US (S2)\n## Impact\n"));
+        assert_eq!(parse_document(&document, &texts).unwrap(), texts);
+
+        let edited = document.replace("A quiet week.", "A busy week.\n<<<# ignored");
+        let parsed = parse_document(&edited, &texts).unwrap();
+        assert_eq!(parsed[0].1, "A busy week.\n\n# Not a comment\n#ops-weekly was told.");
+    }
+
+    #[test]
+    fn removed_headings_are_left_out_and_unknown_ones_refused() {
+        let texts = vec![
+            (DraftText::Cause(0), "Firewall restarted".to_string()),
+            (DraftText::Solution(0), "Recovered automatically".to_string()),
+        ];
+        let parsed = parse_document("<<< Incident 1 solution: anything\nRestarted it\n", &texts).unwrap();
+        assert_eq!(parsed, vec![(DraftText::Solution(0), "Restarted it".to_string())]);
+
+        let error = parse_document("<<< Incident 2 cause\ntext\n", &texts).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown heading \"Incident 2 cause\" in the edited draft");
+        let error = parse_document("stray text\n<<< Incident 1 cause\ntext\n", &texts).unwrap_err();
+        assert_eq!(error.to_string(), "Text before the first heading in the edited draft: stray text");
+    }
+
+    #[test]
+    fn diff_shows_changes_with_context() {
+        let old = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\n";
+        let new = "one\ntwo\nTHREE\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+        assert_eq!(
+            render_diff(old, new, "draft 1", "draft 2"),
+            "--- draft 1\n+++ draft 2\n\
+             @@ line 1 @@\n one\n two\n-three\n+THREE\n four\n five\n\
+             @@ line 8 @@\n eight\n nine\n+ten\n"
+        );
+        assert_eq!(render_diff(old, old, "draft 1", "draft 2"), "");
+    }
+}
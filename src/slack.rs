@@ -0,0 +1,201 @@
+use serde_json::{json, Value};
+use std::env;
+use std::error::Error;
+
+use crate::actions::{incident_label, PreventativeAction};
+use crate::report::{issue_url, Report, ReportIncident};
+use crate::webhooks::{pack_blocks, post_messages, split_text};
+use crate::OutageRecord;
+
+// Slack's limits for a message posted through an incoming webhook
+const MAX_BLOCKS: usize = 50;
+const MAX_SECTION_CHARS: usize = 3000;
+const MAX_HEADER_CHARS: usize = 150;
+const MAX_FIELDS: usize = 10;
+const MAX_MESSAGE_BYTES: usize = 35_000;
+
+/// Escapes the characters Slack's mrkdwn treats as control characters.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn link(text: &str, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("<{}|{}>", url, escape(text)),
+        None => escape(text),
+    }
+}
+
+fn sections(text: &str) -> Vec<Value> {
+    split_text(text, MAX_SECTION_CHARS)
+        .into_iter()
+        .map(|chunk| json!({"type": "section", "text": {"type": "mrkdwn", "text": chunk}}))
+        .collect()
+}
+
+fn context(text: &str) -> Value {
+    json!({"type": "context", "elements": [{"type": "mrkdwn", "text": text}]})
+}
+
+fn kpi_block(report: &Report) -> Option<Value> {
+    let fields: Vec<Value> = report
+        .metrics
+        .iter()
+        .take(MAX_FIELDS)
+        .map(|row| {
+            let mut text = format!("*{}*\n{}", row.label, escape(&row.value));
+            if let Some(last_week) = row.trend.as_deref().and_then(|trend| trend.split(" | ").next()) {
+                text.push_str(&format!("\n_{}_", escape(last_week)));
+            }
+            json!({"type": "mrkdwn", "text": text})
+        })
+        .collect();
+    (!fields.is_empty()).then(|| json!({"type": "section", "fields": fields}))
+}
+
+fn incident_blocks(incident: &ReportIncident) -> Vec<Value> {
+    let mut text = format!("*{}*", escape(&incident.header()));
+    if let Some(key) = &incident.jira_key {
+        text.push_str(&format!("  {}", link(key, incident.ticket_url())));
+    }
+    if let Some(recurrence) = incident.recurrence {
+        text.push_str(&format!("\n:repeat: _Recurring: {}_", escape(recurrence)));
+    }
+    text.push_str(&format!("\n{}", escape(&incident.body())));
+    sections(&text)
+}
+
+fn action_line(record: &OutageRecord, action: &PreventativeAction) -> String {
+    let mut line = format!(
+        "• *{}:* {}",
+        escape(&incident_label(record)),
+        link(&action.key, issue_url(&record.ticket, &action.key).as_deref())
+    );
+    if let Some(summary) = &action.summary {
+        line.push_str(&format!(" {}", escape(summary.trim())));
+    }
+    line.push_str(&escape(&action.progress()));
+    line
+}
+
+fn action_blocks(report: &Report) -> Vec<Value> {
+    let actions = &report.actions;
+    if actions.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = vec!["*Preventative actions*".to_string()];
+    if actions.incidents.is_empty() {
+        lines.push("None for this week's incidents".to_string());
+    }
+    for (record, incident_actions) in &actions.incidents {
+        if incident_actions.is_empty() {
+            lines.push(format!("• *{}:* no follow-up linked in JIRA", escape(&incident_label(record))));
+        }
+        lines.extend(incident_actions.iter().map(|action| action_line(record, action)));
+    }
+    if !actions.carried_over.is_empty() {
+        lines.push("\n*Open actions carried over from previous weeks*".to_string());
+        lines.extend(actions.carried_over.iter().map(|(record, action)| action_line(record, action)));
+    }
+    sections(&lines.join("\n"))
+}
+
+/// Builds the Block Kit messages for the report: a KPI header, then a section per incident.
+/// Long reports are split over several messages, each marked as a continuation.
+pub fn slack_messages(report: &Report) -> Vec<Value> {
+    let title = format!("{} – Week {}", report.title(), report.week_number);
+    let mut blocks = vec![
+        json!({"type": "header", "text": {"type": "plain_text", "text": title.chars().take(MAX_HEADER_CHARS).collect::<String>()}}),
+        context(&format!(
+            "{} - {}, all times UTC",
+            report.week_start.format("%B %d"),
+            report.week_end.format("%B %d")
+        )),
+    ];
+    if let Some(summary) = report.ai.and_then(|ai| ai.summary.as_deref()) {
+        blocks.extend(sections(&escape(summary)));
+    }
+    blocks.extend(kpi_block(report));
+    blocks.extend(action_blocks(report));
+    blocks.push(json!({"type": "divider"}));
+    if report.incidents.is_empty() {
+        blocks.extend(sections("No incidents this week."));
+    }
+    for incident in &report.incidents {
+        blocks.extend(incident_blocks(incident));
+    }
+
+    // Leave room for the continuation note
+    let parts = pack_blocks(blocks, MAX_BLOCKS - 1, MAX_MESSAGE_BYTES);
+    let count = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut blocks)| {
+            let mut text = title.clone();
+            if count > 1 {
+                text = format!("{} ({}/{})", title, index + 1, count);
+            }
+            if index > 0 {
+                blocks.insert(0, context(&format!("_{}, continued_", escape(&text))));
+            }
+            json!({"text": text, "blocks": blocks})
+        })
+        .collect()
+}
+
+/// Posts the report to `SLACK_WEBHOOK_URL`.
+pub async fn post_to_slack(report: &Report<'_>, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let url = env::var("SLACK_WEBHOOK_URL").ok().filter(|url| !url.trim().is_empty());
+    post_messages("Slack", url.as_deref(), &slack_messages(report), report, dry_run).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::{outage, ReportFixture};
+
+    fn blocks(message: &Value) -> &Vec<Value> {
+        message["blocks"].as_array().unwrap()
+    }
+
+    #[test]
+    fn a_short_report_is_one_message() {
+        let fixture = ReportFixture::new(vec![outage(1, "Nginx failed")]);
+        let messages = slack_messages(&fixture.report());
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["text"], "Weekly Stability Report – Week 41");
+        assert_eq!(blocks(&messages[0])[0]["type"], "header");
+    }
+
+    #[test]
+    fn long_reports_are_numbered_continuations_under_the_block_cap() {
+        let outages = (1..=120).map(|ticket| outage(ticket, "Nginx failed")).collect();
+        let fixture = ReportFixture::new(outages);
+        let messages = slack_messages(&fixture.report());
+        let count = messages.len();
+        assert!(count >= 3, "expected the report to be split, got {} message(s)", count);
+
+        for (index, message) in messages.iter().enumerate() {
+            let text = format!("Weekly Stability Report – Week 41 ({}/{})", index + 1, count);
+            assert_eq!(message["text"], text);
+            assert!(blocks(message).len() <= MAX_BLOCKS);
+            assert!(message.to_string().len() <= MAX_MESSAGE_BYTES + 200);
+
+            let first = &blocks(message)[0];
+            if index == 0 {
+                assert_eq!(first["type"], "header");
+            } else {
+                assert_eq!(first, &context(&format!("_{}, continued_", text)));
+            }
+        }
+
+        let incidents: usize = messages
+            .iter()
+            .flat_map(blocks)
+            .filter(|block| block["text"]["text"].as_str().is_some_and(|text| text.contains("Nginx failed")))
+            .count();
+        assert_eq!(incidents, 120);
+    }
+}
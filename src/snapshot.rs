@@ -0,0 +1,133 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::recurrence::RecurrenceConfig;
+use crate::verify::Finding;
+use crate::OutageRecord;
+
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// One request to the model and what came back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmExchange {
+    pub prompt: String,
+    pub raw_response: Option<String>,
+    /// Whether `raw_response` is a stream transcript rather than a single JSON body
+    #[serde(default)]
+    pub streamed: bool,
+    pub error: Option<String>,
+    /// Whether the response was reused from the LLM cache instead of requested
+    #[serde(default)]
+    pub cached: bool,
+    /// Text written by hand in the dashboard or a draft review, used in place of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited: Option<String>,
+}
+
+/// The exact LLM exchanges behind a report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmSnapshot {
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    pub url: String,
+    pub model: String,
+    /// Template name and version the prompts were rendered from, e.g. `weekly-report@1`
+    #[serde(default)]
+    pub prompt_template: Option<String>,
+    pub system_prompt: String,
+    /// `text` or `structured`, older snapshots are always text
+    #[serde(default = "default_output_mode")]
+    pub output_mode: String,
+    /// One narrative request per outage, in outage order
+    #[serde(default)]
+    pub incidents: Vec<LlmExchange>,
+    /// Summary and recommendations pass
+    #[serde(default)]
+    pub summary: Option<LlmExchange>,
+    /// AI_GUARD mode the response was checked with, older snapshots weren't checked
+    #[serde(default = "default_guard")]
+    pub guard: String,
+    /// Mismatches and unsupported names or numbers found in the AI output
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+}
+
+fn default_provider() -> String {
+    "openai".to_string()
+}
+
+fn default_output_mode() -> String {
+    "text".to_string()
+}
+
+fn default_guard() -> String {
+    "off".to_string()
+}
+
+/// Everything needed to rebuild a report without contacting JIRA or the LLM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub week_number: u32,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    /// Earliest outage date in the CSV at the time of the run, used for trend windows
+    pub data_since: Option<NaiveDate>,
+    pub outages: Vec<OutageRecord>,
+    pub history_outages: Vec<OutageRecord>,
+    /// Recurrence settings the report was generated with, not stored by older snapshots
+    #[serde(default)]
+    pub recurrence: Option<RecurrenceConfig>,
+    pub jira_payloads: BTreeMap<String, String>,
+    pub llm: Option<LlmSnapshot>,
+    pub output: String,
+}
+
+impl Snapshot {
+    /// Writes the snapshot to `SNAPSHOT_DIR` and returns its path.
+    pub fn save(&self) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = env::var("SNAPSHOT_DIR").unwrap_or_else(|_| "snapshots".to_string());
+        fs::create_dir_all(&dir)?;
+
+        let path = Path::new(&dir).join(format!(
+            "week-{}-{:02}-{}.json",
+            self.week_start.format("%G"),
+            self.week_number,
+            self.created_at.format("%Y%m%dT%H%M%SZ")
+        ));
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let snapshot: Snapshot = serde_json::from_str(&contents)?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(format!(
+                "Snapshot version {} is newer than supported version {}",
+                snapshot.version, SNAPSHOT_VERSION
+            )
+            .into());
+        }
+        Ok(snapshot)
+    }
+}
+
+impl LlmSnapshot {
+    /// The prompts sent for the report, used to spot text the model copied from them.
+    /// The summary prompt is left out because it quotes the generated narratives.
+    pub fn prompt_text(&self) -> String {
+        let mut text = self.system_prompt.clone();
+        for exchange in &self.incidents {
+            text.push('\n');
+            text.push_str(&exchange.prompt);
+        }
+        text
+    }
+}
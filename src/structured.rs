@@ -0,0 +1,337 @@
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+use crate::prompts::PromptOutage;
+use crate::{format_description, format_outage_header, format_recurrence_line, OutageRecord};
+
+/// Whether the model writes the whole report as free text or returns per-incident JSON that we format ourselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    Structured,
+    Text,
+}
+
+impl OutputMode {
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "structured" | "json" => Ok(OutputMode::Structured),
+            "text" => Ok(OutputMode::Text),
+            other => Err(format!("Unknown LLM output mode '{}'", other).into()),
+        }
+    }
+}
+
+impl fmt::Display for OutputMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputMode::Structured => write!(f, "structured"),
+            OutputMode::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// Narrative for a single incident.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentResponse {
+    pub narrative: String,
+}
+
+/// A prevention suggestion from the model, for internal follow-up only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub text: String,
+    /// 1-based index of the incident it applies to, if any
+    #[serde(default)]
+    pub incident: Option<usize>,
+    #[serde(default)]
+    pub service: Option<String>,
+}
+
+/// Result of the summary pass that runs after the incident narratives.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AiSummary {
+    pub summary: String,
+    #[serde(default)]
+    pub recommendations: Vec<Recommendation>,
+}
+
+/// JSON schema for a single incident narrative, sent to providers that support constrained output.
+pub fn incident_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "narrative": { "type": "string" }
+        },
+        "required": ["narrative"],
+        "additionalProperties": false
+    })
+}
+
+/// JSON schema for the summary and recommendations pass.
+pub fn summary_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "recommendations": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "text": { "type": "string" },
+                        "incident": { "type": ["integer", "null"] }
+                    },
+                    "required": ["text", "incident"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["summary", "recommendations"],
+        "additionalProperties": false
+    })
+}
+
+/// Slices out the JSON object, tolerating code fences or chatter around it.
+fn json_object(content: &str) -> Result<&str, Box<dyn Error>> {
+    let start = content.find('{').ok_or("LLM response contains no JSON object")?;
+    let end = content.rfind('}').ok_or("LLM response contains no JSON object")?;
+    if end < start {
+        return Err("LLM response contains no JSON object".into());
+    }
+    Ok(&content[start..=end])
+}
+
+/// Extracts an incident narrative from the model's content.
+pub fn parse_incident_narrative(mode: OutputMode, content: &str) -> Result<String, Box<dyn Error>> {
+    let narrative = match mode {
+        OutputMode::Text => content.trim().to_string(),
+        OutputMode::Structured => {
+            let response: IncidentResponse = serde_json::from_str(json_object(content)?)
+                .map_err(|e| format!("LLM response is not valid incident JSON: {}", e))?;
+            response.narrative.trim().to_string()
+        }
+    };
+
+    if narrative.is_empty() {
+        Err("LLM returned an empty narrative".into())
+    } else {
+        Ok(narrative)
+    }
+}
+
+/// Parses `- ` bullets, each optionally starting with `[Incident N]`.
+fn parse_recommendation_bullets(text: &str) -> Vec<Recommendation> {
+    let incident_pattern = Regex::new(r"(?i)^\[incident\s+(\d+)\]\s*").expect("incident pattern is valid");
+
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("- "))
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match incident_pattern.captures(line) {
+            Some(captures) => Recommendation {
+                text: line[captures[0].len()..].trim().to_string(),
+                incident: captures[1].parse().ok(),
+                service: None,
+            },
+            None => Recommendation {
+                text: line.to_string(),
+                incident: None,
+                service: None,
+            },
+        })
+        .collect()
+}
+
+/// Extracts the summary and recommendations. In text mode they are separated by a `Recommendations:` line.
+pub fn parse_summary(mode: OutputMode, content: &str) -> Result<AiSummary, Box<dyn Error>> {
+    let summary = match mode {
+        OutputMode::Structured => serde_json::from_str(json_object(content)?)
+            .map_err(|e| format!("LLM response is not valid summary JSON: {}", e))?,
+        OutputMode::Text => {
+            let (summary, recommendations) = match content.find("Recommendations:") {
+                Some(split) => (&content[..split], &content[split + "Recommendations:".len()..]),
+                None => (content, ""),
+            };
+            AiSummary {
+                summary: summary.trim().to_string(),
+                recommendations: parse_recommendation_bullets(recommendations),
+            }
+        }
+    };
+
+    if summary.summary.trim().is_empty() && summary.recommendations.is_empty() {
+        Err("LLM returned an empty summary".into())
+    } else {
+        Ok(summary)
+    }
+}
+
+/// Formats per-incident narratives under headers built from the source data, so dates, times,
+/// services and severities always match the CSV. Incidents without a narrative use the standard entry.
+pub fn render_ai_report(
+    outages: &[OutageRecord],
+    prompt_outages: &[PromptOutage],
+    narratives: &[Option<String>],
+    summary: Option<&str>,
+    week_number: u32,
+    week_start: &NaiveDate,
+    week_end: &NaiveDate,
+) -> String {
+    let mut text = String::new();
+    text.push_str(&format!(
+        "Week {} ({} - {})\nAll times UTC\n\n",
+        week_number,
+        week_start.format("%B %d"),
+        week_end.format("%B %d")
+    ));
+
+    if let Some(summary) = summary {
+        text.push_str(&format!("{}\n\n", summary));
+    }
+
+    for ((record, prompt_outage), narrative) in outages.iter().zip(prompt_outages).zip(narratives) {
+        let header = format_outage_header(
+            record,
+            Some(prompt_outage.start_time.clone()),
+            Some(prompt_outage.end_time.clone()),
+        );
+        let body = match narrative {
+            Some(narrative) => narrative.clone(),
+            None => format_description(&record.cause, &record.solution),
+        };
+        text.push_str(&format!(
+            "{}\n{}{}\n\n",
+            header,
+            format_recurrence_line(prompt_outage.recurrence.as_deref()),
+            body
+        ));
+    }
+
+    text.push_str("Regards,");
+    text
+}
+
+/// Ties each recommendation to an incident and its service. Unknown incident numbers are dropped,
+/// and recommendations without one are matched by the service they mention, if exactly one does.
+pub fn link_recommendations(recommendations: Vec<Recommendation>, outages: &[OutageRecord]) -> Vec<Recommendation> {
+    recommendations
+        .into_iter()
+        .map(|mut recommendation| {
+            recommendation.incident = recommendation.incident.filter(|&id| id >= 1 && id <= outages.len());
+            match recommendation.incident {
+                Some(id) => recommendation.service = Some(outages[id - 1].service.clone()),
+                None => {
+                    let text = recommendation.text.to_lowercase();
+                    let services: BTreeSet<&str> = outages
+                        .iter()
+                        .map(|record| record.service.as_str())
+                        .filter(|service| !service.is_empty() && text.contains(&service.to_lowercase()))
+                        .collect();
+                    let mut services = services.into_iter();
+                    if let (Some(service), None) = (services.next(), services.next()) {
+                        recommendation.service = Some(service.to_string());
+                        let mut incidents = outages.iter().enumerate().filter(|(_, record)| record.service == service);
+                        if let (Some((index, _)), None) = (incidents.next(), incidents.next()) {
+                            recommendation.incident = Some(index + 1);
+                        }
+                    }
+                }
+            }
+            recommendation
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_incident_narratives() {
+        let narrative = parse_incident_narrative(OutputMode::Structured, r#"{"narrative": " Nginx was restarted. "}"#);
+        assert_eq!(narrative.unwrap(), "Nginx was restarted.");
+
+        let fenced = "Here is the narrative:\n```json\n{\"narrative\": \"Nginx was restarted.\"}\n```";
+        assert_eq!(parse_incident_narrative(OutputMode::Structured, fenced).unwrap(), "Nginx was restarted.");
+
+        assert_eq!(parse_incident_narrative(OutputMode::Text, "\n Nginx was restarted.\n").unwrap(), "Nginx was restarted.");
+    }
+
+    #[test]
+    fn rejects_incident_narratives_without_text() {
+        let missing = parse_incident_narrative(OutputMode::Structured, r#"{"summary": "Nginx was restarted."}"#);
+        assert!(missing.unwrap_err().to_string().contains("not valid incident JSON"));
+
+        let no_json = parse_incident_narrative(OutputMode::Structured, "Nginx was restarted.");
+        assert_eq!(no_json.unwrap_err().to_string(), "LLM response contains no JSON object");
+
+        let empty = parse_incident_narrative(OutputMode::Structured, r#"{"narrative": "  "}"#);
+        assert_eq!(empty.unwrap_err().to_string(), "LLM returned an empty narrative");
+        assert!(parse_incident_narrative(OutputMode::Text, " \n").is_err());
+    }
+
+    #[test]
+    fn parses_summaries_and_recommendations() {
+        let structured = "```json\n{\"summary\": \"A quiet week.\", \"recommendations\": [\
+            {\"text\": \"Alert on Nginx restarts\", \"incident\": 2}, {\"text\": \"Review runbooks\", \"incident\": null}]}\n```";
+        let summary = parse_summary(OutputMode::Structured, structured).unwrap();
+        assert_eq!(summary.summary, "A quiet week.");
+        let recommendations: Vec<(&str, Option<usize>)> =
+            summary.recommendations.iter().map(|r| (r.text.as_str(), r.incident)).collect();
+        assert_eq!(recommendations, vec![("Alert on Nginx restarts", Some(2)), ("Review runbooks", None)]);
+
+        let text = "A quiet week.\n\nRecommendations:\n- [Incident 2] Alert on Nginx restarts\n- Review runbooks\n";
+        let summary = parse_summary(OutputMode::Text, text).unwrap();
+        assert_eq!(summary.summary, "A quiet week.");
+        let recommendations: Vec<(&str, Option<usize>)> =
+            summary.recommendations.iter().map(|r| (r.text.as_str(), r.incident)).collect();
+        assert_eq!(recommendations, vec![("Alert on Nginx restarts", Some(2)), ("Review runbooks", None)]);
+    }
+
+    #[test]
+    fn links_recommendations_to_the_one_service_they_mention() {
+        use crate::report::fixture::outage;
+
+        let on = |ticket, service: &str| OutageRecord { service: service.to_string(), ..outage(ticket, "Nginx failed") };
+        let outages = vec![on(1, "Billing"), on(2, "API"), on(3, "Billing"), on(4, "Search")];
+        let recommendation = |text: &str, incident| Recommendation { text: text.to_string(), incident, service: None };
+
+        let linked = link_recommendations(
+            vec![
+                recommendation("Alert on Billing queue depth", None),
+                recommendation("Load test Search", None),
+                recommendation("Share the Billing and API runbooks", None),
+                recommendation("Review the timeouts", Some(2)),
+                recommendation("Check the Billing retries", Some(9)),
+            ],
+            &outages,
+        );
+        let targets: Vec<(Option<usize>, Option<&str>)> =
+            linked.iter().map(|r| (r.incident, r.service.as_deref())).collect();
+        assert_eq!(
+            targets,
+            vec![
+                // Billing had two incidents, so only the service is known
+                (None, Some("Billing")),
+                (Some(4), Some("Search")),
+                (None, None),
+                (Some(2), Some("API")),
+                (None, Some("Billing")),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_summaries_with_missing_fields() {
+        let missing = parse_summary(OutputMode::Structured, r#"{"recommendations": []}"#);
+        assert!(missing.unwrap_err().to_string().contains("not valid summary JSON"));
+
+        let empty = parse_summary(OutputMode::Structured, r#"{"summary": "", "recommendations": []}"#);
+        assert_eq!(empty.unwrap_err().to_string(), "LLM returned an empty summary");
+    }
+}
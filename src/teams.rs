@@ -0,0 +1,209 @@
+use serde_json::{json, Value};
+use std::env;
+use std::error::Error;
+
+use crate::actions::{incident_label, PreventativeAction};
+use crate::report::{issue_url, Report, ReportIncident};
+use crate::webhooks::{pack_blocks, post_messages, split_text};
+use crate::OutageRecord;
+
+// Teams rejects webhook payloads over 28 KB; the rest is headroom for the card envelope
+const MAX_CARD_BYTES: usize = 24_000;
+const MAX_TEXT_CHARS: usize = 4000;
+
+fn link(text: &str, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("[{}]({})", text, url),
+        None => text.to_string(),
+    }
+}
+
+fn text_block(text: &str) -> Value {
+    json!({"type": "TextBlock", "text": text, "wrap": true})
+}
+
+/// Adaptive Card colour for a severity badge.
+fn severity_color(severity: &str) -> &'static str {
+    match severity.trim() {
+        "S1" => "Attention",
+        "S2" => "Warning",
+        _ => "Default",
+    }
+}
+
+fn kpi_facts(report: &Report) -> Option<Value> {
+    let facts: Vec<Value> = report
+        .metrics
+        .iter()
+        .map(|row| {
+            let value = match &row.trend {
+                Some(trend) => format!("{} ({})", row.value, trend),
+                None => row.value.clone(),
+            };
+            json!({"title": row.label, "value": value})
+        })
+        .collect();
+    (!facts.is_empty()).then(|| json!({"type": "FactSet", "facts": facts}))
+}
+
+fn incident_container(incident: &ReportIncident) -> Vec<Value> {
+    let mut items = vec![json!({
+        "type": "TextBlock",
+        "text": incident.header(),
+        "weight": "Bolder",
+        "color": severity_color(&incident.record.severity),
+        "wrap": true
+    })];
+    if let Some(key) = &incident.jira_key {
+        items.push(json!({"type": "TextBlock", "text": link(key, incident.ticket_url()), "isSubtle": true, "spacing": "None"}));
+    }
+    if let Some(recurrence) = incident.recurrence {
+        items.push(json!({"type": "TextBlock", "text": format!("Recurring: {}", recurrence), "color": "Warning", "wrap": true}));
+    }
+
+    // A long narrative continues in further containers, so each stays small enough to move between cards
+    let mut chunks = split_text(&incident.body(), MAX_TEXT_CHARS).into_iter();
+    items.extend(chunks.next().map(|chunk| text_block(&chunk)));
+    let mut containers = vec![json!({"type": "Container", "separator": true, "spacing": "Medium", "items": items})];
+    containers.extend(chunks.map(|chunk| json!({"type": "Container", "items": [text_block(&chunk)]})));
+    containers
+}
+
+fn action_text(record: &OutageRecord, action: &PreventativeAction) -> String {
+    let mut line = format!(
+        "- **{}:** {}",
+        incident_label(record),
+        link(&action.key, issue_url(&record.ticket, &action.key).as_deref())
+    );
+    if let Some(summary) = &action.summary {
+        line.push_str(&format!(" {}", summary.trim()));
+    }
+    line.push_str(&action.progress());
+    line
+}
+
+fn action_blocks(report: &Report) -> Vec<Value> {
+    let actions = &report.actions;
+    if actions.is_empty() {
+        return Vec::new();
+    }
+
+    let mut blocks = vec![json!({"type": "TextBlock", "text": "Preventative actions", "weight": "Bolder", "separator": true})];
+    if actions.incidents.is_empty() {
+        blocks.push(text_block("None for this week's incidents"));
+    }
+    for (record, incident_actions) in &actions.incidents {
+        if incident_actions.is_empty() {
+            blocks.push(text_block(&format!("- **{}:** no follow-up linked in JIRA", incident_label(record))));
+        }
+        blocks.extend(incident_actions.iter().map(|action| text_block(&action_text(record, action))));
+    }
+    if !actions.carried_over.is_empty() {
+        blocks.push(json!({"type": "TextBlock", "text": "Open actions carried over from previous weeks", "weight": "Bolder"}));
+        blocks.extend(actions.carried_over.iter().map(|(record, action)| text_block(&action_text(record, action))));
+    }
+    blocks
+}
+
+fn card_message(body: Vec<Value>) -> Value {
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "contentUrl": null,
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "msteams": {"width": "Full"},
+                "body": body
+            }
+        }]
+    })
+}
+
+/// Builds Adaptive Card messages for the report, split into several cards when it's too big for one.
+pub fn teams_messages(report: &Report) -> Vec<Value> {
+    let title = format!("{} – Week {}", report.title(), report.week_number);
+    let mut blocks = vec![
+        json!({"type": "TextBlock", "text": title, "size": "Large", "weight": "Bolder", "wrap": true}),
+        json!({
+            "type": "TextBlock",
+            "text": format!("{} - {}, all times UTC", report.week_start.format("%B %d"), report.week_end.format("%B %d")),
+            "isSubtle": true,
+            "spacing": "None"
+        }),
+    ];
+    if let Some(summary) = report.ai.and_then(|ai| ai.summary.as_deref()) {
+        blocks.push(text_block(summary));
+    }
+    blocks.extend(kpi_facts(report));
+    blocks.extend(action_blocks(report));
+    if report.incidents.is_empty() {
+        blocks.push(text_block("No incidents this week."));
+    }
+    for incident in &report.incidents {
+        blocks.extend(incident_container(incident));
+    }
+
+    let parts = pack_blocks(blocks, usize::MAX, MAX_CARD_BYTES);
+    let count = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut body)| {
+            if index > 0 {
+                body.insert(
+                    0,
+                    json!({"type": "TextBlock", "text": format!("{}, continued ({}/{})", title, index + 1, count), "isSubtle": true}),
+                );
+            }
+            card_message(body)
+        })
+        .collect()
+}
+
+/// Posts the report to `TEAMS_WEBHOOK_URL`.
+pub async fn post_to_teams(report: &Report<'_>, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let url = env::var("TEAMS_WEBHOOK_URL").ok().filter(|url| !url.trim().is_empty());
+    post_messages("Teams", url.as_deref(), &teams_messages(report), report, dry_run).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::{outage, ReportFixture};
+
+    fn body(message: &Value) -> &Vec<Value> {
+        message["attachments"][0]["content"]["body"].as_array().unwrap()
+    }
+
+    #[test]
+    fn a_short_report_is_one_card() {
+        let fixture = ReportFixture::new(vec![outage(1, "Nginx failed")]);
+        let messages = teams_messages(&fixture.report());
+        assert_eq!(messages.len(), 1);
+        assert_eq!(body(&messages[0])[0]["text"], "Weekly Stability Report – Week 41");
+    }
+
+    #[test]
+    fn large_reports_are_split_into_numbered_cards() {
+        let cause = "Database failover took longer than expected. ".repeat(40);
+        let outages = (1..=20).map(|ticket| outage(ticket, &cause)).collect();
+        let fixture = ReportFixture::new(outages);
+        let messages = teams_messages(&fixture.report());
+        let count = messages.len();
+        assert!(count >= 2, "expected the report to be split, got {} card(s)", count);
+
+        for (index, message) in messages.iter().enumerate() {
+            assert!(serde_json::to_string(body(message)).unwrap().len() <= MAX_CARD_BYTES + 200);
+            let first = &body(message)[0]["text"];
+            if index == 0 {
+                assert_eq!(first, "Weekly Stability Report – Week 41");
+            } else {
+                let continued = format!("Weekly Stability Report – Week 41, continued ({}/{})", index + 1, count);
+                assert_eq!(first, continued.as_str());
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+use crate::metrics::{compute_weekly_metrics, format_minutes, WeeklyMetrics};
+use crate::{outages_in_range, JiraIssue, OutageRecord};
+
+pub const TRAILING_WEEKS_SHORT: usize = 4;
+pub const TRAILING_WEEKS_LONG: usize = 13;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kpi {
+    IncidentCount,
+    TotalDowntime,
+    MeanTimeToResolve,
+    MedianTimeToResolve,
+    LongestIncident,
+    MeanTimeToDetect,
+}
+
+impl Kpi {
+    pub fn value(&self, metrics: &WeeklyMetrics) -> Option<f64> {
+        match self {
+            Kpi::IncidentCount => Some(metrics.incident_count as f64),
+            Kpi::TotalDowntime => Some(metrics.total_downtime_minutes as f64),
+            Kpi::MeanTimeToResolve => metrics.mean_time_to_resolve,
+            Kpi::MedianTimeToResolve => metrics.median_time_to_resolve,
+            Kpi::LongestIncident => metrics.longest_incident.as_ref().map(|l| l.minutes as f64),
+            Kpi::MeanTimeToDetect => metrics.mean_time_to_detect,
+        }
+    }
+
+    fn format_value(&self, value: f64) -> String {
+        match self {
+            Kpi::IncidentCount => {
+                if value.fract() == 0.0 {
+                    format!("{}", value as i64)
+                } else {
+                    format!("{:.1}", value)
+                }
+            }
+            _ => format_minutes(value),
+        }
+    }
+}
+
+/// Metrics for the weeks preceding the report week, most recent first.
+#[derive(Debug, Clone, Default)]
+pub struct TrendComparison {
+    pub previous_weeks: Vec<WeeklyMetrics>,
+}
+
+impl TrendComparison {
+    pub fn last_week(&self, kpi: Kpi) -> Option<f64> {
+        self.previous_weeks.first().and_then(|m| kpi.value(m))
+    }
+
+    pub fn trailing_average(&self, kpi: Kpi, weeks: usize) -> Option<f64> {
+        let values: Vec<f64> = self
+            .previous_weeks
+            .iter()
+            .take(weeks)
+            .filter_map(|m| kpi.value(m))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    pub fn describe(&self, kpi: Kpi, current: Option<f64>) -> Option<String> {
+        let current = current?;
+        let mut parts = Vec::new();
+
+        parts.extend(describe_change(kpi, Some(current), self.last_week(kpi), "last week"));
+        if let Some(avg) = self.trailing_average(kpi, TRAILING_WEEKS_SHORT) {
+            parts.push(format!("{}-wk avg {}", TRAILING_WEEKS_SHORT, kpi.format_value(avg)));
+        }
+        if self.previous_weeks.len() > TRAILING_WEEKS_SHORT {
+            if let Some(avg) = self.trailing_average(kpi, TRAILING_WEEKS_LONG) {
+                parts.push(format!("{}-wk avg {}", TRAILING_WEEKS_LONG, kpi.format_value(avg)));
+            }
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" | "))
+        }
+    }
+}
+
+/// Describes the change from an earlier period, e.g. "▲ +2 (+50%) vs last month".
+pub fn describe_change(kpi: Kpi, current: Option<f64>, previous: Option<f64>, period: &str) -> Option<String> {
+    Some(format!("{} vs {}", format_delta(kpi, current?, previous?), period))
+}
+
+fn format_delta(kpi: Kpi, current: f64, previous: f64) -> String {
+    let delta = current - previous;
+    if delta.abs() < f64::EPSILON {
+        return "= no change".to_string();
+    }
+
+    let arrow = if delta > 0.0 { "▲" } else { "▼" };
+    let sign = if delta > 0.0 { "+" } else { "-" };
+    let amount = format!("{}{}", sign, kpi.format_value(delta.abs()));
+
+    if previous > 0.0 {
+        format!("{} {} ({}{:.0}%)", arrow, amount, sign, (delta.abs() / previous) * 100.0)
+    } else {
+        format!("{} {}", arrow, amount)
+    }
+}
+
+/// Computes metrics for each of the trailing weeks before `week_start`.
+/// Weeks ending before `data_since`, the earliest record in the CSV, are skipped so they don't count as zero-incident weeks.
+pub fn compute_trends(
+    history_outages: &[OutageRecord],
+    jira_details: &HashMap<String, JiraIssue>,
+    week_start: &NaiveDate,
+    data_since: Option<NaiveDate>,
+) -> TrendComparison {
+    let mut previous_weeks = Vec::new();
+    if let Some(earliest) = data_since {
+        for weeks_back in 1..=TRAILING_WEEKS_LONG {
+            let start = *week_start - Duration::weeks(weeks_back as i64);
+            let end = start + Duration::days(6);
+            if end < earliest {
+                break;
+            }
+            let outages = outages_in_range(history_outages, &start, &end);
+            previous_weeks.push(compute_weekly_metrics(&outages, jira_details));
+        }
+    }
+
+    TrendComparison { previous_weeks }
+}
@@ -0,0 +1,336 @@
+use chrono::{Datelike, Duration};
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::error::Error;
+use std::fmt;
+
+use crate::prompts::PromptOutage;
+use crate::{extract_jira_key, get_week_number, parse_date, parse_duration_to_minutes, JiraIssue, OutageRecord};
+
+/// What to do when the AI text mentions names or numbers that aren't in the source data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardMode {
+    /// Use the standard entry for such incidents, leave such a summary out, and report them
+    Correct,
+    /// Leave the text alone and only report problems
+    Flag,
+    Off,
+}
+
+impl GuardMode {
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "correct" => Ok(GuardMode::Correct),
+            "flag" => Ok(GuardMode::Flag),
+            "off" => Ok(GuardMode::Off),
+            other => Err(format!("Unknown AI_GUARD mode '{}'", other).into()),
+        }
+    }
+
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        Self::parse(&env::var("AI_GUARD").unwrap_or_else(|_| "correct".to_string()))
+    }
+}
+
+impl fmt::Display for GuardMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuardMode::Correct => write!(f, "correct"),
+            GuardMode::Flag => write!(f, "flag"),
+            GuardMode::Off => write!(f, "off"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// 1-based index of the incident whose narrative it is in, `None` for the summary
+    pub incident: Option<usize>,
+    pub message: String,
+    pub corrected: bool,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.incident {
+            Some(incident) => write!(f, "Incident {}: ", incident)?,
+            None => write!(f, "Summary: ")?,
+        }
+        write!(f, "{}", self.message)?;
+        if self.corrected {
+            write!(f, " (left out)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The narratives and summary after the check, with what it found.
+pub struct Verification {
+    pub narratives: Vec<Option<String>>,
+    pub summary: Option<String>,
+    pub findings: Vec<Finding>,
+}
+
+// Words that are fine to appear capitalized without being in the source data
+const ALLOWED_WORDS: &[&str] = &[
+    "utc", "ceo", "cto", "api", "ai", "regards", "week", "we", "our", "this", "the", "a", "an", "i",
+    "monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday",
+    "january", "february", "march", "april", "may", "june", "july", "august", "september",
+    "october", "november", "december", "sept", "jan", "feb", "mar", "apr", "jun", "jul", "aug",
+    "sep", "oct", "nov", "dec", "rca", "jira",
+];
+
+fn word_pattern() -> Regex {
+    Regex::new(r"\b[A-Z][A-Za-z]+\b").expect("word pattern is valid")
+}
+
+fn number_pattern() -> Regex {
+    Regex::new(r"\b\d+(?:[:.]\d+)?\b").expect("number pattern is valid")
+}
+
+fn normalize_number(number: &str) -> String {
+    match number.split_once(':') {
+        // "9:05" and "09:05" are the same time
+        Some((h, m)) => format!("{:0>2}:{}", h, m),
+        None if number.contains('.') => number.to_string(),
+        None => match number.trim_start_matches('0') {
+            "" => "0".to_string(),
+            trimmed => trimmed.to_string(),
+        },
+    }
+}
+
+/// The words and numbers in the source data, each as a whole token.
+struct SourceFacts {
+    words: HashSet<String>,
+    numbers: HashSet<String>,
+}
+
+impl SourceFacts {
+    fn new(outages: &[OutageRecord], prompt_outages: &[PromptOutage], jira_details: &HashMap<String, JiraIssue>) -> Self {
+        let mut text = String::new();
+        let mut numbers = HashSet::new();
+        let mut total_minutes = 0;
+
+        for (record, prompt_outage) in outages.iter().zip(prompt_outages) {
+            for field in [
+                &record.date,
+                &record.service,
+                &record.duration,
+                &record.cause,
+                &record.solution,
+                &record.severity,
+                &prompt_outage.start_time,
+                &prompt_outage.end_time,
+                &prompt_outage.jira_rca,
+            ] {
+                text.push_str(field);
+                text.push('\n');
+            }
+            if let Some(description) = extract_jira_key(&record.ticket)
+                .and_then(|key| jira_details.get(&key))
+                .and_then(|issue| issue.fields.description.as_ref())
+            {
+                text.push_str(description);
+                text.push('\n');
+            }
+            if let Some(recurrence) = &prompt_outage.recurrence {
+                text.push_str(recurrence);
+                text.push('\n');
+            }
+
+            // Durations like "4h29m" can be written as 269 minutes or as 4 hours 29 minutes
+            let minutes = parse_duration_to_minutes(&record.duration);
+            total_minutes += minutes;
+            numbers.extend([minutes, minutes / 60, minutes % 60].map(|n| n.to_string()));
+            if let Some(date) = parse_date(&record.date) {
+                // Report weeks start on Sunday
+                let week_start = date - Duration::days(date.weekday().num_days_from_sunday() as i64);
+                numbers.extend([date.day(), date.year() as u32, get_week_number(&week_start)].map(|n| n.to_string()));
+            }
+        }
+        numbers.insert(total_minutes.to_string());
+        numbers.extend((1..=outages.len()).map(|n| n.to_string()));
+        numbers.extend(number_pattern().find_iter(&text).map(|number| normalize_number(number.as_str())));
+
+        let words = Regex::new(r"[A-Za-z]+")
+            .expect("word pattern is valid")
+            .find_iter(&text)
+            .map(|word| word.as_str().to_lowercase())
+            .collect();
+        SourceFacts { words, numbers }
+    }
+}
+
+fn is_sentence_start(text: &str, position: usize) -> bool {
+    let before = text[..position].trim_end();
+    before.is_empty() || before.ends_with(['.', '!', '?', ':', '-'])
+}
+
+/// Capitalized words used mid-sentence in the prompt, i.e. the names the examples mention.
+fn prompt_names(prompt: &str) -> HashSet<String> {
+    word_pattern()
+        .find_iter(prompt)
+        .filter(|word| !is_sentence_start(prompt, word.start()))
+        .map(|word| word.as_str().to_lowercase())
+        .collect()
+}
+
+/// Finds capitalized words and numbers in each paragraph of `text` that don't appear in the source data.
+fn unsupported_tokens(text: &str, facts: &SourceFacts, prompt_names: &HashSet<String>) -> Vec<String> {
+    let mut findings = Vec::new();
+    let mut seen = HashSet::new();
+
+    for paragraph in text.split("\n\n").map(str::trim).filter(|paragraph| !paragraph.is_empty()) {
+        for word in word_pattern().find_iter(paragraph) {
+            let lower = word.as_str().to_lowercase();
+            let from_prompt = prompt_names.contains(&lower);
+            // Sentence-initial words are capitalized anyway, unless they're a name from the examples
+            if is_sentence_start(paragraph, word.start()) && !from_prompt {
+                continue;
+            }
+            if ALLOWED_WORDS.contains(&lower.as_str()) || facts.words.contains(&lower) || !seen.insert(lower.clone()) {
+                continue;
+            }
+            if from_prompt {
+                findings.push(format!("name \"{}\" is not in the source data and looks copied from the prompt examples", word.as_str()));
+            } else {
+                findings.push(format!("name \"{}\" is not in the source data", word.as_str()));
+            }
+        }
+
+        for number in number_pattern().find_iter(paragraph) {
+            let value = normalize_number(number.as_str());
+            if facts.numbers.contains(&value) || !seen.insert(value) {
+                continue;
+            }
+            findings.push(format!("number \"{}\" is not in the source data", number.as_str()));
+        }
+    }
+
+    findings
+}
+
+/// Checks the AI narratives and summary for names and numbers that appear nowhere in the incidents they were
+/// generated from. Headings are built from the source data, so they aren't checked.
+pub fn verify_ai_report(
+    narratives: Vec<Option<String>>,
+    summary: Option<String>,
+    outages: &[OutageRecord],
+    prompt_outages: &[PromptOutage],
+    jira_details: &HashMap<String, JiraIssue>,
+    prompt: &str,
+    mode: GuardMode,
+) -> Verification {
+    if mode == GuardMode::Off {
+        return Verification { narratives, summary, findings: Vec::new() };
+    }
+
+    let correct = mode == GuardMode::Correct;
+    let facts = SourceFacts::new(outages, prompt_outages, jira_details);
+    let prompt_names = prompt_names(prompt);
+    let mut findings = Vec::new();
+    let mut check = |incident: Option<usize>, text: Option<String>| {
+        let problems = text.as_deref().map(|text| unsupported_tokens(text, &facts, &prompt_names)).unwrap_or_default();
+        let rejected = correct && !problems.is_empty();
+        findings.extend(problems.into_iter().map(|message| Finding { incident, message, corrected: correct }));
+        text.filter(|_| !rejected)
+    };
+
+    let summary = check(None, summary);
+    let narratives = narratives
+        .into_iter()
+        .enumerate()
+        .map(|(index, narrative)| check(Some(index + 1), narrative))
+        .collect();
+
+    for finding in &findings {
+        warn!("AI report check: {}", finding);
+    }
+
+    Verification { narratives, summary, findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::{outage, ReportFixture};
+
+    const PROMPT: &str = "For example, Rachel from Market confirmed the fix at 14:00.";
+
+    fn verify_with(mode: GuardMode, duration: &str, narrative: &str, summary: &str) -> Verification {
+        let fixture = ReportFixture::new(vec![OutageRecord {
+            duration: duration.to_string(),
+            ..outage(1004, "Nginx failed at 14:00")
+        }]);
+        verify_ai_report(
+            vec![Some(narrative.to_string())],
+            Some(summary.to_string()),
+            &fixture.outages,
+            &fixture.prompt_outages,
+            &fixture.jira_details,
+            PROMPT,
+            mode,
+        )
+    }
+
+    fn verify(mode: GuardMode, narrative: &str, summary: &str) -> Verification {
+        verify_with(mode, "15", narrative, summary)
+    }
+
+    fn messages(verification: &Verification) -> Vec<String> {
+        verification.findings.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn flags_names_and_numbers_in_every_paragraph_and_the_summary() {
+        // "4" is part of 14:00 and OPS-1004, but not a number of its own in the source data
+        let narrative = "Nginx failed at 14:00 on Sales-I US.\n\nThe fix was confirmed by Rachel from Market after 4 retries.";
+        let verification = verify(GuardMode::Flag, narrative, "Week 41 had 1 incident, resolved by Bob in 15 minutes.");
+
+        assert_eq!(
+            messages(&verification),
+            vec![
+                "Summary: name \"Bob\" is not in the source data",
+                "Incident 1: name \"Rachel\" is not in the source data and looks copied from the prompt examples",
+                "Incident 1: name \"Market\" is not in the source data and looks copied from the prompt examples",
+                "Incident 1: number \"4\" is not in the source data",
+            ]
+        );
+        assert_eq!(verification.narratives, vec![Some(narrative.to_string())]);
+        assert!(verification.summary.is_some());
+    }
+
+    #[test]
+    fn accepts_numbers_that_are_whole_tokens_of_the_source_data() {
+        let narrative = "Nginx failed at 14:00 on October 12, 2026 and was down for 4 hours 29 minutes, or 269 minutes.";
+        let verification = verify_with(GuardMode::Flag, "4h29m", narrative, "One S2 incident on Sales-I US.");
+        assert_eq!(messages(&verification), Vec::<String>::new());
+    }
+
+    #[test]
+    fn correct_leaves_out_the_text_it_flags() {
+        let verification = verify(GuardMode::Correct, "Rachel from Market restarted Nginx.", "It was fixed by Bob.");
+
+        assert_eq!(verification.narratives, vec![None]);
+        assert_eq!(verification.summary, None);
+        assert_eq!(verification.findings.len(), 3);
+        assert!(verification.findings.iter().all(|finding| finding.corrected));
+        assert_eq!(messages(&verification)[0], "Summary: name \"Bob\" is not in the source data (left out)");
+
+        let clean = verify(GuardMode::Correct, "Nginx was restarted.", "One incident on Sales-I US.");
+        assert_eq!(clean.narratives, vec![Some("Nginx was restarted.".to_string())]);
+        assert!(clean.summary.is_some() && clean.findings.is_empty());
+    }
+
+    #[test]
+    fn off_checks_nothing() {
+        let verification = verify(GuardMode::Off, "Rachel from Market restarted Nginx.", "Bob fixed it.");
+        assert!(verification.findings.is_empty());
+        assert_eq!(verification.narratives, vec![Some("Rachel from Market restarted Nginx.".to_string())]);
+        assert_eq!(verification.summary.as_deref(), Some("Bob fixed it."));
+    }
+}
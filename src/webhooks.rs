@@ -0,0 +1,201 @@
+use log::info;
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
+
+use crate::report::{outbox_dir, Report};
+
+/// Splits text into chunks of at most `max_chars` characters, preferring paragraph, line and word breaks.
+pub fn split_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+
+    while rest.chars().count() > max_chars {
+        let limit = rest.char_indices().nth(max_chars).map(|(index, _)| index).unwrap_or(rest.len());
+        let head = &rest[..limit];
+        let cut = ["\n\n", "\n", " "]
+            .iter()
+            .find_map(|separator| head.rfind(separator).filter(|&index| index > 0))
+            .unwrap_or(limit);
+        chunks.push(rest[..cut].trim_end().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        chunks.push(rest.to_string());
+    }
+    chunks
+}
+
+/// Packs blocks into as few messages as possible, keeping each under `max_blocks` blocks and
+/// `max_bytes` of JSON. A block that is too big on its own still gets a message to itself.
+pub fn pack_blocks(blocks: Vec<Value>, max_blocks: usize, max_bytes: usize) -> Vec<Vec<Value>> {
+    let mut messages = Vec::new();
+    let mut current: Vec<Value> = Vec::new();
+    let mut current_bytes = 0;
+
+    for block in blocks {
+        let bytes = block.to_string().len();
+        if !current.is_empty() && (current.len() >= max_blocks || current_bytes + bytes > max_bytes) {
+            messages.push(std::mem::take(&mut current));
+            current_bytes = 0;
+        }
+        current_bytes += bytes;
+        current.push(block);
+    }
+    if !current.is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+/// Posts each message to the webhook in order, or writes them to `OUTBOX_DIR` on a dry run.
+pub async fn post_messages(
+    name: &str,
+    url: Option<&str>,
+    messages: &[Value],
+    report: &Report<'_>,
+    dry_run: bool,
+) -> Result<(), Box<dyn Error>> {
+    if dry_run {
+        let dir = outbox_dir();
+        fs::create_dir_all(&dir)?;
+        for (index, message) in messages.iter().enumerate() {
+            let path = dir.join(format!("{}-{}-{}.json", report.file_stem(), name.to_lowercase(), index + 1));
+            fs::write(&path, serde_json::to_string_pretty(message)?)?;
+            info!("Dry run: wrote {} message {}/{} to {}", name, index + 1, messages.len(), path.display());
+        }
+        return Ok(());
+    }
+
+    let url = url.ok_or_else(|| format!("{}_WEBHOOK_URL must be set to post the report", name.to_uppercase()))?;
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+    for (index, message) in messages.iter().enumerate() {
+        let response = client.post(url).json(message).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!(
+                "{} rejected message {}/{} with {}: {}",
+                name,
+                index + 1,
+                messages.len(),
+                status,
+                body.trim()
+            )
+            .into());
+        }
+    }
+    info!("Posted the report to {} in {} message(s)", name, messages.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::fixture::{outage, ReportFixture};
+    use serde_json::json;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn splits_multibyte_text_at_the_character_limit() {
+        let text = "é".repeat(25);
+        let chunks = split_text(&text, 10);
+        assert_eq!(chunks, vec!["é".repeat(10), "é".repeat(10), "é".repeat(5)]);
+    }
+
+    #[test]
+    fn prefers_paragraph_then_line_then_word_breaks() {
+        assert_eq!(split_text("ab cd\nef gh\n\nij", 12), vec!["ab cd\nef gh", "ij"]);
+        assert_eq!(split_text("ab cd\nef gh", 8), vec!["ab cd", "ef gh"]);
+        assert_eq!(split_text("ab cd ef", 6), vec!["ab cd", "ef"]);
+        assert_eq!(split_text("  short  ", 10), vec!["short"]);
+    }
+
+    #[test]
+    fn an_oversized_block_gets_a_message_to_itself() {
+        let big = json!({"text": "x".repeat(100)});
+        let small = json!({"text": "y"});
+        let messages = pack_blocks(vec![small.clone(), big.clone(), small.clone()], 50, 50);
+        assert_eq!(messages, vec![vec![small.clone()], vec![big], vec![small]]);
+    }
+
+    #[test]
+    fn caps_the_blocks_per_message() {
+        let blocks: Vec<Value> = (0..120).map(|index| json!({"index": index})).collect();
+        let messages = pack_blocks(blocks, 50, usize::MAX);
+        let sizes: Vec<usize> = messages.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![50, 50, 20]);
+        assert_eq!(messages[2][0], json!({"index": 100}));
+    }
+
+    /// Answers each POST like a webhook, with the next of `statuses`, returning the bodies received.
+    async fn webhook_stand_in(listener: TcpListener, statuses: Vec<u16>) -> Vec<Value> {
+        let mut bodies = Vec::new();
+        for status in statuses {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await.unwrap();
+            bodies.push(serde_json::from_slice(&body).unwrap());
+
+            let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok", status);
+            writer.write_all(response.as_bytes()).await.unwrap();
+        }
+        bodies
+    }
+
+    #[tokio::test]
+    async fn posts_every_message_in_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(webhook_stand_in(listener, vec![200, 200, 200]));
+
+        let fixture = ReportFixture::new(vec![outage(1, "Cause")]);
+        let messages: Vec<Value> = (1..=3).map(|part| json!({"part": part})).collect();
+        post_messages("Slack", Some(&url), &messages, &fixture.report(), false).await.unwrap();
+
+        assert_eq!(server.await.unwrap(), messages);
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_first_rejected_message() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(webhook_stand_in(listener, vec![200, 400]));
+
+        let fixture = ReportFixture::new(vec![outage(1, "Cause")]);
+        let messages: Vec<Value> = (1..=3).map(|part| json!({"part": part})).collect();
+        let error = post_messages("Teams", Some(&url), &messages, &fixture.report(), false)
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().starts_with("Teams rejected message 2/3 with 400"), "{}", error);
+        assert_eq!(server.await.unwrap(), messages[..2]);
+    }
+
+    #[tokio::test]
+    async fn a_missing_url_is_an_error() {
+        let fixture = ReportFixture::new(vec![outage(1, "Cause")]);
+        let error = post_messages("Slack", None, &[json!({})], &fixture.report(), false)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "SLACK_WEBHOOK_URL must be set to post the report");
+    }
+}